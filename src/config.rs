@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const FILENAME: &str = ".norme.toml";
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+	Integer(i64),
+	Boolean(bool),
+	String(String),
+	Array(Vec<Value>)
+}
+
+impl Value {
+	fn type_name(&self) -> &'static str {
+		match *self {
+			Value::Integer(_) => "an integer",
+			Value::Boolean(_) => "a boolean",
+			Value::String(_) => "a string",
+			Value::Array(_) => "an array"
+		}
+	}
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+	pub line: usize,//0 when no line of the file is concerned.
+	pub message: String
+}
+
+impl ConfigError {
+	pub fn new<S: Into<String>>(line: usize, message: S) -> ConfigError {
+		ConfigError { line, message: message.into() }
+	}
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.line > 0 {
+			write!(f, "line {}: {}", self.line, self.message)
+		}
+		else {
+			write!(f, "{}", self.message)
+		}
+	}
+}
+
+
+#[derive(Debug, Clone)]
+struct Table {
+	line: usize,
+	entries: BTreeMap<String, (usize, Value)>
+}
+
+//Subset of TOML: tables, and keys holding integers, booleans, strings or arrays of those.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+	tables: BTreeMap<String, Table>,//Keyed by their dotted name, "" for the keys before any table.
+	pub directory: PathBuf//Where the file is, the paths it gives are relative to it. Empty when there isn't any.
+}
+
+impl Config {
+	pub fn load(path: &Path) -> Result<Config, ConfigError> {
+		let content = fs::read_to_string(path).map_err(|error| ConfigError::new(0, error.to_string()))?;
+		let mut config = Config::parse(&content)?;
+		config.directory = path.parent().map_or(PathBuf::new(), Path::to_path_buf);
+		return Ok(config);
+	}
+
+	pub fn parse(content: &str) -> Result<Config, ConfigError> {
+		let mut config = Config::default();
+		let mut current = String::new();
+
+		for (line_number, line) in (1..).zip(content.lines()) {
+			let line = strip_comment(line).trim();
+			if line.is_empty() {
+				continue;
+			}
+
+			if line.starts_with('[') {
+				if !line.ends_with(']') || line.starts_with("[[") {
+					return Err(ConfigError::new(line_number, "Invalid table header."));
+				}
+				current = line[1..line.len() - 1].trim().to_string();
+				if current.is_empty() || !current.split('.').all(is_bare_key) {
+					return Err(ConfigError::new(line_number, format!("Invalid table name '{}'.", current)));
+				}
+				if config.tables.contains_key(&current) {
+					return Err(ConfigError::new(line_number, format!("Table '{}' defined twice.", current)));
+				}
+				config.tables.insert(current.clone(), Table { line: line_number, entries: BTreeMap::new() });
+				continue;
+			}
+
+			let (key, value) = match line.find('=') {
+				Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+				None => return Err(ConfigError::new(line_number, "Expected 'key = value'."))
+			};
+			if !is_bare_key(key) {
+				return Err(ConfigError::new(line_number, format!("Invalid key '{}'.", key)));
+			}
+
+			let (value, rest) = parse_value(value).map_err(|message| ConfigError::new(line_number, message))?;
+			if !rest.trim().is_empty() {
+				return Err(ConfigError::new(line_number, format!("Unexpected '{}' after the value.", rest.trim())));
+			}
+
+			let table = config.tables.entry(current.clone()).or_insert(Table { line: line_number, entries: BTreeMap::new() });
+			if table.entries.insert(key.to_string(), (line_number, value)).is_some() {
+				return Err(ConfigError::new(line_number, format!("Key '{}' defined twice.", key)));
+			}
+		}
+
+		return Ok(config);
+	}
+
+	pub fn integer(&self, table: &str, key: &str, default: usize) -> Result<usize, ConfigError> {
+		match self.tables.get(table).and_then(|t| t.entries.get(key)) {
+			None => Ok(default),
+			Some(&(_, Value::Integer(value))) if value >= 1 => Ok(value as usize),
+			Some(&(line, Value::Integer(value))) => Err(ConfigError::new(line, format!("'{}' must be at least 1, got {}.", key, value))),
+			Some(&(line, ref value)) => Err(ConfigError::new(line, format!("'{}' must be a positive integer, got {}.", key, value.type_name())))
+		}
+	}
+
+	pub fn boolean(&self, table: &str, key: &str, default: bool) -> Result<bool, ConfigError> {
+		match self.tables.get(table).and_then(|t| t.entries.get(key)) {
+			None => Ok(default),
+			Some(&(_, Value::Boolean(value))) => Ok(value),
+			Some(&(line, ref value)) => Err(ConfigError::new(line, format!("'{}' must be a boolean, got {}.", key, value.type_name())))
+		}
+	}
+
+	pub fn strings(&self, table: &str, key: &str) -> Result<Option<Vec<String>>, ConfigError> {
+		let (line, value) = match self.tables.get(table).and_then(|t| t.entries.get(key)) {
+			Some(&(line, ref value)) => (line, value),
+			None => return Ok(None)
+		};
+
+		let error = || ConfigError::new(line, format!("'{}' must be an array of strings.", key));
+		match *value {
+			Value::Array(ref values) => values.iter().map(|v| match *v {
+				Value::String(ref s) => Ok(s.clone()),
+				_ => Err(error())
+			}).collect::<Result<Vec<String>, ConfigError>>().map(Some),
+			_ => Err(error())
+		}
+	}
+
+	//Names of the tables nested right under the given one, with the line they are defined at.
+	pub fn subtables(&self, table: &str) -> Vec<(&str, usize)> {
+		let prefix = format!("{}.", table);
+		self.tables.iter()
+			.filter(|&(name, _)| name.starts_with(&prefix) && !name[prefix.len()..].contains('.'))
+			.map(|(name, t)| (&name[prefix.len()..], t.line))
+			.collect()
+	}
+
+	//Keys of the table, with the line they are defined at.
+	pub fn keys(&self, table: &str) -> Vec<(&str, usize)> {
+		self.tables.get(table).map_or(Vec::new(), |t| t.entries.iter().map(|(key, &(line, _))| (key.as_str(), line)).collect())
+	}
+}
+
+
+//Looks for the configuration file in the directory and all its parents.
+pub fn discover(start: &Path) -> Option<PathBuf> {
+	let start = fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+	let directory = if start.is_dir() { start.as_path() } else { start.parent()? };
+
+	directory.ancestors().map(|d| d.join(FILENAME)).find(|path| path.is_file())
+}
+
+fn is_bare_key(key: &str) -> bool {
+	!key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn strip_comment(line: &str) -> &str {
+	let mut in_string = false;
+	let mut escaped = false;
+
+	for (index, c) in line.char_indices() {
+		if escaped {
+			escaped = false;
+			continue;
+		}
+		match c {
+			'\\' if in_string => escaped = true,
+			'"' => in_string = !in_string,
+			'#' if !in_string => return &line[..index],
+			_ => {}
+		}
+	}
+
+	return line;
+}
+
+//Returns the value and what remains of the text after it.
+fn parse_value(text: &str) -> Result<(Value, &str), String> {
+	let text = text.trim_start();
+
+	if let Some(rest) = text.strip_prefix('"') {
+		let mut value = String::new();
+		let mut chars = rest.char_indices();
+		while let Some((index, c)) = chars.next() {
+			match c {
+				'"' => return Ok((Value::String(value), &rest[index + 1..])),
+				'\\' => match chars.next() {
+					Some((_, 'n')) => value.push('\n'),
+					Some((_, 't')) => value.push('\t'),
+					Some((_, '"')) => value.push('"'),
+					Some((_, '\\')) => value.push('\\'),
+					_ => return Err(String::from("Invalid escape sequence in string."))
+				},
+				_ => value.push(c)
+			}
+		}
+		return Err(String::from("Unterminated string."));
+	}
+	else if let Some(mut rest) = text.strip_prefix('[') {
+		let mut values = Vec::new();
+		loop {
+			rest = rest.trim_start();
+			if let Some(after) = rest.strip_prefix(']') {
+				return Ok((Value::Array(values), after));
+			}
+
+			let (value, after) = parse_value(rest)?;
+			values.push(value);
+			rest = after.trim_start();
+			if let Some(after) = rest.strip_prefix(',') {
+				rest = after;
+			}
+			else if !rest.starts_with(']') {
+				return Err(String::from("Expected ',' or ']' in array."));
+			}
+		}
+	}
+
+	let end = text.find(|c: char| c == ',' || c == ']' || c.is_whitespace()).unwrap_or(text.len());
+	let word = &text[..end];
+	let value = match word {
+		"true" => Value::Boolean(true),
+		"false" => Value::Boolean(false),
+		_ => match word.replace('_', "").parse::<i64>() {
+			Ok(number) => Value::Integer(number),
+			Err(_) => return Err(format!("Invalid value '{}'.", word))
+		}
+	};
+
+	return Ok((value, &text[end..]));
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn parse() {
+		let config = Config::parse("# comment\nnames = [\"a # b\"]\n\n[rules]\nignore = [\"Goto\", \"Enum\"] # trailing\n[rules.LineSize]\nmax = 1_00\nstrict = true\n").unwrap();
+
+		assert_eq!(config.strings("", "names"), Ok(Some(vec![String::from("a # b")])));
+		assert_eq!(config.strings("rules", "ignore"), Ok(Some(vec![String::from("Goto"), String::from("Enum")])));
+		assert_eq!(config.integer("rules.LineSize", "max", 80), Ok(100));
+		assert_eq!(config.integer("rules.LineSize", "missing", 80), Ok(80));
+		assert_eq!(config.keys("rules.LineSize"), vec![("max", 7), ("strict", 8)]);
+		assert!(config.strings("rules.LineSize", "strict").is_err());
+		assert_eq!(config.boolean("rules.LineSize", "strict", false), Ok(true));
+		assert!(config.boolean("rules.LineSize", "max", false).is_err());
+		assert_eq!(config.subtables("rules"), vec![("LineSize", 6)]);
+	}
+
+	#[test]
+	fn errors() {
+		assert_eq!(Config::parse("[rules\n").unwrap_err().line, 1);
+		assert_eq!(Config::parse("a = 1\nb\n").unwrap_err().line, 2);
+		assert_eq!(Config::parse("a = \"open\n").unwrap_err().line, 1);
+		assert_eq!(Config::parse("a = 1\na = 2\n").unwrap_err().line, 2);
+		assert_eq!(Config::parse("a = [1, 2\n").unwrap_err().line, 1);
+
+		let config = Config::parse("[rules.LineSize]\nmax = -1\n").unwrap();
+		let error = config.integer("rules.LineSize", "max", 80).unwrap_err();
+		assert_eq!((error.line, error.message.as_str()), (2, "'max' must be at least 1, got -1."));
+		let config = Config::parse("[rules.IndentationLevel]\nspaces = 0\n").unwrap();
+		assert_eq!(config.integer("rules.IndentationLevel", "spaces", 4).unwrap_err().message, "'spaces' must be at least 1, got 0.");
+		let config = Config::parse("[rules.LineSize]\nmax = \"80\"\n").unwrap();
+		assert_eq!(config.integer("rules.LineSize", "max", 80).unwrap_err().message, "'max' must be a positive integer, got a string.");
+	}
+}
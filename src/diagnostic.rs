@@ -0,0 +1,71 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+	Warning,
+	Error
+}
+
+impl Severity {
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Severity::Warning => "warning",
+			Severity::Error => "error"
+		}
+	}
+}
+
+
+//Replacement of a byte range of the checked content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+	pub start: usize,
+	pub end: usize,//Excluded, equal to start for an insertion.
+	pub replacement: String
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+	pub rule: &'static str,
+	pub filename: String,
+	pub line: Option<usize>,//None when the whole file is concerned.
+	pub columns: Option<(usize, usize)>,//Byte range on the line, starting at 0, end excluded.
+	pub severity: Severity,
+	pub message: String,
+	pub fix: Vec<Edit>//Applied all together to correct the diagnostic, empty when it must be done by hand.
+}
+
+impl Diagnostic {
+	pub fn new<S: Into<String>>(rule: &'static str, filename: &str, line: usize, message: S) -> Diagnostic {
+		Diagnostic {
+			rule,
+			filename: String::from(filename),
+			line: Some(line),
+			columns: None,
+			severity: Severity::Error,
+			message: message.into(),
+			fix: Vec::new()
+		}
+	}
+
+	pub fn file<S: Into<String>>(rule: &'static str, filename: &str, message: S) -> Diagnostic {
+		Diagnostic {
+			rule,
+			filename: String::from(filename),
+			line: None,
+			columns: None,
+			severity: Severity::Error,
+			message: message.into(),
+			fix: Vec::new()
+		}
+	}
+
+	pub fn columns(mut self, start: usize, end: usize) -> Diagnostic {
+		self.columns = Some((start, end.max(start + 1)));
+		self
+	}
+
+	pub fn edit<S: Into<String>>(mut self, start: usize, end: usize, replacement: S) -> Diagnostic {
+		self.fix.push(Edit { start, end, replacement: replacement.into() });
+		self
+	}
+}
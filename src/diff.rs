@@ -0,0 +1,256 @@
+//Lines of unchanged content shown around each change.
+const CONTEXT: usize = 3;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+	Equal(usize, usize),//Indexes of the line in the old and the new content.
+	Delete(usize),
+	Insert(usize)
+}
+
+//Unified diff between the two contents of the file, empty when they are the same.
+pub fn unified(filename: &str, old: &str, new: &str) -> String {
+	let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+	let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+	let operations = diff(&old_lines, &new_lines);
+
+	let mut patch = String::new();
+	let changes: Vec<usize> = operations.iter().enumerate()
+		.filter(|&(_, operation)| !matches!(*operation, Operation::Equal(_, _)))
+		.map(|(index, _)| index)
+		.collect();
+	if changes.is_empty() {
+		return patch;
+	}
+	patch.push_str(&format!("--- {}\n+++ {}\n", filename, filename));
+
+	//Changes closer than twice the context are shown in the same hunk.
+	let mut index = 0;
+	while index < changes.len() {
+		let mut last = index;
+		while last + 1 < changes.len() && changes[last + 1] - changes[last] <= 2 * CONTEXT {
+			last += 1;
+		}
+		let start = changes[index].saturating_sub(CONTEXT);
+		let end = (changes[last] + CONTEXT + 1).min(operations.len());
+		hunk(&mut patch, &operations[start..end], &old_lines, &new_lines);
+		index = last + 1;
+	}
+
+	return patch;
+}
+
+fn hunk(patch: &mut String, operations: &[Operation], old_lines: &[&str], new_lines: &[&str]) {
+	let mut old_start = None;
+	let mut new_start = None;
+	let (mut old_count, mut new_count) = (0, 0);
+
+	for operation in operations.iter() {
+		match *operation {
+			Operation::Equal(old, new) => {
+				old_start.get_or_insert(old);
+				new_start.get_or_insert(new);
+				old_count += 1;
+				new_count += 1;
+			},
+			Operation::Delete(old) => {
+				old_start.get_or_insert(old);
+				old_count += 1;
+			},
+			Operation::Insert(new) => {
+				new_start.get_or_insert(new);
+				new_count += 1;
+			}
+		}
+	}
+	//Lines are numbered from 1. With the context, a side can only be empty when the whole file is, its range is then "0,0".
+	let old_start = old_start.map_or(0, |start| start + 1);
+	let new_start = new_start.map_or(0, |start| start + 1);
+	patch.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+
+	for operation in operations.iter() {
+		let (prefix, line) = match *operation {
+			Operation::Equal(old, _) => (' ', old_lines[old]),
+			Operation::Delete(old) => ('-', old_lines[old]),
+			Operation::Insert(new) => ('+', new_lines[new])
+		};
+		patch.push(prefix);
+		patch.push_str(line);
+		if !line.ends_with('\n') {
+			patch.push_str("\n\\ No newline at end of file\n");
+		}
+	}
+}
+
+//Shortest edit script between the lines, from "An O(ND) Difference Algorithm and Its Variations" (Myers), in its
+//linear space variant: the middle snake of the path is searched for from both ends, then each half is diffed alone.
+fn diff(old: &[&str], new: &[&str]) -> Vec<Operation> {
+	let mut path = vec![(0, 0)];
+	find_path(old, new, (0, 0), (old.len() as isize, new.len() as isize), &mut path);
+	if path.last() != Some(&(old.len() as isize, new.len() as isize)) {
+		path.push((old.len() as isize, new.len() as isize));
+	}
+
+	//Between two points of the path, there are at most one insertion or deletion, surrounded by equal lines.
+	let mut operations = Vec::new();
+	for pair in path.windows(2) {
+		let ((mut x, mut y), (end_x, end_y)) = (pair[0], pair[1]);
+		let diagonal = |x: &mut isize, y: &mut isize, operations: &mut Vec<Operation>| {
+			while *x < end_x && *y < end_y && old[*x as usize] == new[*y as usize] {
+				operations.push(Operation::Equal(*x as usize, *y as usize));
+				*x += 1;
+				*y += 1;
+			}
+		};
+		diagonal(&mut x, &mut y, &mut operations);
+		if end_x - x < end_y - y {
+			operations.push(Operation::Insert(y as usize));
+			y += 1;
+		}
+		else if end_x - x > end_y - y {
+			operations.push(Operation::Delete(x as usize));
+			x += 1;
+		}
+		diagonal(&mut x, &mut y, &mut operations);
+	}
+
+	return operations;
+}
+
+//Points of the path from the top left corner of the box, excluded, to its bottom right one.
+fn find_path(old: &[&str], new: &[&str], start: (isize, isize), end: (isize, isize), path: &mut Vec<(isize, isize)>) {
+	let (snake_start, snake_end) = match middle_snake(old, new, start, end) {
+		Some(snake) => snake,
+		None => return
+	};
+	find_path(old, new, start, snake_start, path);
+	if path.last() != Some(&snake_start) {
+		path.push(snake_start);
+	}
+	path.push(snake_end);
+	find_path(old, new, snake_end, end, path);
+}
+
+//Snake, with the edit before it, where the shortest paths from both corners of the box meet. None for an empty box.
+fn middle_snake(old: &[&str], new: &[&str], (left, top): (isize, isize), (right, bottom): (isize, isize)) -> Option<((isize, isize), (isize, isize))> {
+	let (width, height) = (right - left, bottom - top);
+	if width + height == 0 {
+		return None;
+	}
+	let max = (width + height + 1) / 2;
+	let delta = width - height;
+	let index = |k: isize| (k + max + 1) as usize;
+
+	//Furthest x reached forward on each diagonal k = x - y, and furthest y reached backward on each diagonal c = k - delta.
+	let mut forward = vec![0; 2 * max as usize + 3];
+	let mut backward = vec![0; 2 * max as usize + 3];
+	forward[index(1)] = left;
+	backward[index(1)] = bottom;
+
+	for d in 0..=max {
+		for k in (-d..=d).rev().step_by(2) {
+			let c = k - delta;
+			let (previous_x, mut x) = if k == -d || (k != d && forward[index(k - 1)] < forward[index(k + 1)]) {
+				(forward[index(k + 1)], forward[index(k + 1)])
+			}
+			else {
+				(forward[index(k - 1)], forward[index(k - 1)] + 1)
+			};
+			let mut y = top + (x - left) - k;
+			let previous_y = if d == 0 || x != previous_x { y } else { y - 1 };
+			while x < right && y < bottom && old[x as usize] == new[y as usize] {
+				x += 1;
+				y += 1;
+			}
+			forward[index(k)] = x;
+			if delta % 2 != 0 && -d < c && c < d && y >= backward[index(c)] {
+				return Some(((previous_x, previous_y), (x, y)));
+			}
+		}
+
+		for c in (-d..=d).rev().step_by(2) {
+			let k = c + delta;
+			let (previous_y, mut y) = if c == -d || (c != d && backward[index(c - 1)] > backward[index(c + 1)]) {
+				(backward[index(c + 1)], backward[index(c + 1)])
+			}
+			else {
+				(backward[index(c - 1)], backward[index(c - 1)] - 1)
+			};
+			let mut x = left + (y - top) + k;
+			let previous_x = if d == 0 || y != previous_y { x } else { x + 1 };
+			while x > left && y > top && old[x as usize - 1] == new[y as usize - 1] {
+				x -= 1;
+				y -= 1;
+			}
+			backward[index(c)] = y;
+			if delta % 2 == 0 && -d <= k && k <= d && x <= forward[index(k)] {
+				return Some(((x, y), (previous_x, previous_y)));
+			}
+		}
+	}
+
+	return None;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn same() {
+		assert_eq!(unified("a.c", "a\nb\n", "a\nb\n"), "");
+	}
+
+	#[test]
+	fn changes() {
+		let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+		let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n12\n";
+		assert_eq!(unified("a.c", old, new), "--- a.c\n+++ a.c\n\
+			@@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+			@@ -8,5 +8,4 @@\n 8\n 9\n 10\n-11\n 12\n");
+	}
+
+	#[test]
+	fn bounds() {
+		assert_eq!(unified("a.c", "", "a\n"), "--- a.c\n+++ a.c\n@@ -0,0 +1,1 @@\n+a\n");
+		assert_eq!(unified("a.c", "a", "b"), "--- a.c\n+++ a.c\n@@ -1,1 +1,1 @@\n-a\n\\ No newline at end of file\n+b\n\\ No newline at end of file\n");
+		assert_eq!(unified("a.c", "a\nb\n", "a\n"), "--- a.c\n+++ a.c\n@@ -1,2 +1,1 @@\n a\n-b\n");
+	}
+
+	#[test]
+	fn minimal() {
+		//Pseudo random contents of a few distinct lines, compared to the length of their longest common subsequence.
+		let mut seed: u32 = 1;
+		let mut lines = |count: usize| -> Vec<&str> {
+			(0..count).map(|_| {
+				seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+				["a\n", "b\n", "c\n"][(seed >> 16) as usize % 3]
+			}).collect()
+		};
+		for size in 0..40 {
+			let (old, new) = (lines(size), lines(size / 2 + 3));
+			let operations = diff(&old, &new);
+
+			let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+			for x in (0..old.len()).rev() {
+				for y in (0..new.len()).rev() {
+					common[x][y] = if old[x] == new[y] { common[x + 1][y + 1] + 1 } else { common[x + 1][y].max(common[x][y + 1]) };
+				}
+			}
+			let equal = operations.iter().filter(|o| matches!(**o, Operation::Equal(_, _))).count();
+			assert_eq!(equal, common[0][0]);
+			assert_eq!(operations.len(), old.len() + new.len() - equal);
+
+			let rebuilt: Vec<&str> = operations.iter().filter_map(|o| match *o {
+				Operation::Equal(_, y) | Operation::Insert(y) => Some(new[y]),
+				Operation::Delete(_) => None
+			}).collect();
+			assert_eq!(rebuilt, new);
+			let kept: Vec<&str> = operations.iter().filter_map(|o| match *o {
+				Operation::Equal(x, _) | Operation::Delete(x) => Some(old[x]),
+				Operation::Insert(_) => None
+			}).collect();
+			assert_eq!(kept, old);
+		}
+	}
+}
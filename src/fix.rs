@@ -0,0 +1,80 @@
+use diagnostic::{Diagnostic, Edit};
+use rules;
+use rules::Rule;
+use source::{FileKind, SourceFile};
+
+//Fixing a diagnostic can reveal or create others, checking again stops once nothing changes.
+const MAX_PASSES: usize = 10;
+
+
+//Applies the fixes of the diagnostics, skipping those that overlap a fix of a previous diagnostic.
+//Returns None when there was nothing to apply.
+pub fn apply(content: &str, diagnostics: &[Diagnostic]) -> Option<String> {
+	let mut accepted: Vec<&Edit> = Vec::new();
+
+	for diagnostic in diagnostics.iter().filter(|d| !d.fix.is_empty()) {
+		let overlaps = diagnostic.fix.iter().any(|edit| accepted.iter().any(|other| {
+			(edit.start < other.end && other.start < edit.end) || edit.start == other.start
+		}));
+		let valid = diagnostic.fix.iter().all(|edit| {
+			edit.start <= edit.end && edit.end <= content.len() && content.is_char_boundary(edit.start) && content.is_char_boundary(edit.end)
+		});
+
+		if !overlaps && valid {
+			accepted.extend(diagnostic.fix.iter());
+		}
+	}
+	if accepted.is_empty() {
+		return None;
+	}
+
+	accepted.sort_by_key(|edit| (edit.start, edit.end));
+	let mut fixed = String::with_capacity(content.len());
+	let mut position = 0;
+	for edit in accepted {
+		fixed.push_str(&content[position..edit.start]);
+		fixed.push_str(&edit.replacement);
+		position = edit.end;
+	}
+	fixed.push_str(&content[position..]);
+
+	return Some(fixed);
+}
+
+//Content of the file once every fix that could be applied has been.
+pub fn fix(rules: &[Box<dyn Rule>], filename: &str, kind: FileKind, content: &str) -> String {
+	let mut content = String::from(content);
+
+	for _ in 0..MAX_PASSES {
+		let fixed = {
+			let file = SourceFile::new(filename, kind, &content);
+			apply(&content, &rules::check_all(rules, &file))
+		};
+		match fixed {
+			Some(fixed) if fixed != content => content = fixed,
+			_ => break
+		}
+	}
+
+	return content;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn overlapping() {
+		let diagnostics = vec![
+			Diagnostic::new("A", "", 1, "").edit(0, 2, "x"),
+			Diagnostic::new("B", "", 1, "").edit(1, 3, "y"),
+			Diagnostic::new("C", "", 1, "").edit(3, 3, "z").edit(5, 6, ""),
+			Diagnostic::new("D", "", 1, "").edit(3, 3, "w"),
+			Diagnostic::new("E", "", 1, "")
+		];
+
+		assert_eq!(apply("abcdef", &diagnostics), Some(String::from("xczde")));
+		assert_eq!(apply("abcdef", &diagnostics[4..]), None);
+	}
+}
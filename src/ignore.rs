@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+//Read in every walked directory, with the syntax of '.gitignore'.
+pub const FILENAME: &str = ".normeignore";
+pub const GITIGNORE: &str = ".gitignore";
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+	glob: Vec<char>,
+	negated: bool,//"!pattern" includes again what a previous one ignored.
+	directory_only: bool,//"pattern/"
+	anchored: bool//Matched against the path from the base when it has a '/', else against the name only.
+}
+
+
+//Patterns of an ignore file, relative to the directory it is in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ignore {
+	base: PathBuf,
+	patterns: Vec<Pattern>
+}
+
+impl Ignore {
+	pub fn new<S: AsRef<str>>(base: &Path, lines: &[S]) -> Ignore {
+		let mut patterns = Vec::new();
+
+		for line in lines.iter() {
+			let mut line = line.as_ref();
+			//Trailing spaces are ignored unless escaped.
+			while line.ends_with(' ') && !line.ends_with("\\ ") {
+				line = &line[..line.len() - 1];
+			}
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let negated = line.starts_with('!');
+			if negated || line.starts_with("\\!") || line.starts_with("\\#") {
+				line = &line[1..];
+			}
+			let directory_only = line.ends_with('/') && line.len() > 1;
+			if directory_only {
+				line = &line[..line.len() - 1];
+			}
+			let anchored = line.contains('/');
+			let glob = line.trim_start_matches('/').chars().collect();
+
+			patterns.push(Pattern { glob, negated, directory_only, anchored });
+		}
+
+		return Ignore { base: base.to_path_buf(), patterns };
+	}
+
+	//The file of the directory, None when there is none or it can't be read.
+	pub fn load(directory: &Path, filename: &str) -> Option<Ignore> {
+		let content = fs::read_to_string(directory.join(filename)).ok()?;
+		let lines: Vec<&str> = content.lines().collect();
+		Some(Ignore::new(directory, &lines))
+	}
+
+	//Whether the last matching pattern ignores the path, None when none matches. Patterns with a '/' only match the
+	//paths under the base.
+	pub fn matched(&self, path: &Path, is_directory: bool) -> Option<bool> {
+		let relative: Option<Vec<char>> = path.strip_prefix(&self.base).ok().map(|r| r.to_string_lossy().replace('\\', "/").chars().collect());
+		let name: Vec<char> = path.file_name()?.to_string_lossy().chars().collect();
+
+		self.patterns.iter().rev()
+			.filter(|p| is_directory || !p.directory_only)
+			.find(|p| match (p.anchored, relative.as_ref()) {
+				(false, _) => matches(&p.glob, &name, true),
+				(true, Some(relative)) => matches(&p.glob, relative, true),
+				(true, None) => false
+			})
+			.map(|p| !p.negated)
+	}
+}
+
+
+//Last match of the ignore files, from the outermost to the innermost.
+pub fn is_ignored(ignores: &[Ignore], path: &Path, is_directory: bool) -> bool {
+	ignores.iter().rev().find_map(|ignore| ignore.matched(path, is_directory)).unwrap_or(false)
+}
+
+//"*" and "?" don't match '/', "[a-z]" and "[!a-z]" are classes, "**" matches any number of directories
+//when it is a whole component.
+fn matches(glob: &[char], text: &[char], component_start: bool) -> bool {
+	if glob.is_empty() {
+		return text.is_empty();
+	}
+
+	if component_start && glob.starts_with(&['*', '*']) && (glob.len() == 2 || glob[2] == '/') {
+		if glob.len() == 2 {
+			return true;
+		}
+		return matches(&glob[3..], text, true)
+			|| text.iter().enumerate().any(|(index, &c)| c == '/' && matches(&glob[3..], &text[index + 1..], true));
+	}
+
+	match glob[0] {
+		'*' => {
+			let mut glob = glob;
+			while glob.first() == Some(&'*') {
+				glob = &glob[1..];
+			}
+			let end = text.iter().position(|&c| c == '/').unwrap_or(text.len());
+			(0..=end).any(|index| matches(glob, &text[index..], false))
+		},
+		'?' => !text.is_empty() && text[0] != '/' && matches(&glob[1..], &text[1..], false),
+		'[' => match class(glob) {
+			Some((matcher, length)) => !text.is_empty() && text[0] != '/' && matcher(text[0]) && matches(&glob[length..], &text[1..], false),
+			None => text.first() == Some(&'[') && matches(&glob[1..], &text[1..], false)
+		},
+		'\\' if glob.len() > 1 => text.first() == Some(&glob[1]) && matches(&glob[2..], &text[1..], false),
+		c => text.first() == Some(&c) && matches(&glob[1..], &text[1..], c == '/')
+	}
+}
+
+//Matcher of the class starting the glob, with the length of the class. None when it isn't closed.
+#[allow(clippy::type_complexity)]
+fn class(glob: &[char]) -> Option<(Box<dyn Fn(char) -> bool>, usize)> {
+	let mut index = 1;
+	let negated = glob.get(index).is_some_and(|&c| c == '!' || c == '^');
+	if negated {
+		index += 1;
+	}
+
+	let mut ranges = Vec::new();
+	let mut first = true;
+	while index < glob.len() && (first || glob[index] != ']') {
+		let start = glob[index];
+		if glob.get(index + 1) == Some(&'-') && glob.get(index + 2).is_some_and(|&c| c != ']') {
+			ranges.push((start, glob[index + 2]));
+			index += 3;
+		}
+		else {
+			ranges.push((start, start));
+			index += 1;
+		}
+		first = false;
+	}
+	if index >= glob.len() {
+		return None;
+	}
+
+	return Some((Box::new(move |c| ranges.iter().any(|&(start, end)| start <= c && c <= end) != negated), index + 1));
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::slice;
+
+	fn glob(pattern: &str, text: &str) -> bool {
+		let pattern: Vec<char> = pattern.chars().collect();
+		let text: Vec<char> = text.chars().collect();
+		matches(&pattern, &text, true)
+	}
+
+	#[test]
+	fn globs() {
+		assert!(glob("*.c", "main.c"));
+		assert!(!glob("*.c", "src/main.c"));
+		assert!(glob("src/*.c", "src/main.c"));
+		assert!(glob("?ain.[ch]", "main.h"));
+		assert!(!glob("main.[!ch]", "main.c"));
+		assert!(glob("main.[a-d]", "main.c"));
+		assert!(glob("**/gen/*.c", "gen/a.c"));
+		assert!(glob("**/gen/*.c", "src/deep/gen/a.c"));
+		assert!(glob("vendor/**", "vendor/lib/a.c"));
+		assert!(glob("a/**/b", "a/x/y/b"));
+		assert!(glob("a/**/b", "a/b"));
+		assert!(!glob("a**b", "a/b"));
+		assert!(glob("\\*.c", "*.c"));
+		assert!(glob("[.c", "[.c"));
+	}
+
+	#[test]
+	fn patterns() {
+		let base = Path::new("/project");
+		let ignore = Ignore::new(base, &["# comment", "", "*.gen.c", "!keep.gen.c", "/build", "tests/fixtures/", "docs/*.h  "]);
+
+		assert_eq!(ignore.matched(&base.join("src/parser.gen.c"), false), Some(true));
+		assert_eq!(ignore.matched(&base.join("src/keep.gen.c"), false), Some(false));
+		assert_eq!(ignore.matched(&base.join("build"), true), Some(true));
+		assert_eq!(ignore.matched(&base.join("src/build"), true), None);
+		assert_eq!(ignore.matched(&base.join("tests/fixtures"), true), Some(true));
+		assert_eq!(ignore.matched(&base.join("tests/fixtures"), false), None);
+		assert_eq!(ignore.matched(&base.join("docs/a.h"), false), Some(true));
+		assert_eq!(ignore.matched(Path::new("/elsewhere/a.gen.c"), false), Some(true));
+		assert_eq!(ignore.matched(Path::new("/elsewhere/build"), true), None);
+
+		let inner = Ignore::new(&base.join("src"), &["!*.gen.c"]);
+		assert!(is_ignored(slice::from_ref(&ignore), &base.join("src/a.gen.c"), false));
+		assert!(!is_ignored(&[ignore, inner], &base.join("src/a.gen.c"), false));
+	}
+}
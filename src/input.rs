@@ -0,0 +1,92 @@
+use std::str;
+
+use diagnostic::Diagnostic;
+
+//Identifiers of the diagnostics reporting files that couldn't be checked.
+pub const IO_ERROR_ID: &str = "IoError";
+pub const ENCODING_ID: &str = "InvalidEncoding";
+
+//Characters of the bytes 0x80 to 0x9F in Windows-1252, the 5 undefined ones being kept as control characters.
+const WINDOWS_1252: [char; 32] = [
+	'€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+	'\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ'
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+	Utf8,
+	Windows1252//Also covers Latin-1, of which it is a superset for the printable characters.
+}
+
+impl Encoding {
+	pub fn from_name(name: &str) -> Option<Encoding> {
+		match name.to_ascii_lowercase().as_str() {
+			"utf-8" | "utf8" => Some(Encoding::Utf8),
+			"windows-1252" | "cp1252" | "latin1" | "latin-1" | "iso-8859-1" => Some(Encoding::Windows1252),
+			_ => None
+		}
+	}
+}
+
+
+//Content of the file with the encoding it was read with. Files that aren't valid UTF-8 are decoded with the fallback,
+//or reported at their first invalid byte when it is UTF-8 too.
+pub fn decode(filename: &str, bytes: &[u8], fallback: Encoding) -> Result<(String, Encoding), Box<Diagnostic>> {
+	let error = match str::from_utf8(bytes) {
+		Ok(content) => return Ok((String::from(content), Encoding::Utf8)),
+		Err(error) => error
+	};
+	if fallback == Encoding::Windows1252 {
+		let content = bytes.iter().map(|&byte| match byte {
+			0x80..=0x9F => WINDOWS_1252[byte as usize - 0x80],
+			_ => byte as char
+		}).collect();
+		return Ok((content, Encoding::Windows1252));
+	}
+
+	let valid = error.valid_up_to();
+	let line = 1 + bytes[..valid].iter().filter(|&&b| b == b'\n').count();
+	let column = valid - bytes[..valid].iter().rposition(|&b| b == b'\n').map_or(0, |index| index + 1);
+	let message = format!("Invalid UTF-8 byte 0x{:02X}, the file isn't checked. Use '--encoding windows-1252' for Latin-1 files.", bytes[valid]);
+
+	return Err(Box::new(Diagnostic::new(ENCODING_ID, filename, line, message).columns(column, column + 1)));
+}
+
+//Bytes of the content in the encoding, None when a character can't be represented in it.
+pub fn encode(content: &str, encoding: Encoding) -> Option<Vec<u8>> {
+	match encoding {
+		Encoding::Utf8 => Some(content.as_bytes().to_vec()),
+		Encoding::Windows1252 => content.chars().map(|c| match c as u32 {
+			code @ 0x80..=0x9F if WINDOWS_1252[code as usize - 0x80] == c => Some(code as u8),
+			0x80..=0x9F => None,
+			code @ 0..=0xFF => Some(code as u8),
+			_ => WINDOWS_1252.iter().position(|&w| w == c).map(|index| (index + 0x80) as u8)
+		}).collect()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn utf8() {
+		assert_eq!(decode("a.c", "int é;".as_bytes(), Encoding::Utf8), Ok((String::from("int é;"), Encoding::Utf8)));
+
+		let error = decode("a.c", b"int x;\n/* \xE9t\xE9 */", Encoding::Utf8).unwrap_err();
+		assert_eq!((error.rule, error.line, error.columns), (ENCODING_ID, Some(2), Some((3, 4))));
+	}
+
+	#[test]
+	fn windows_1252() {
+		let bytes = b"/* \xE9t\xE9 \x80 \x81 */".to_vec();
+		let (content, encoding) = decode("a.c", &bytes, Encoding::Windows1252).unwrap();
+		assert_eq!((content.as_str(), encoding), ("/* été € \u{81} */", Encoding::Windows1252));
+		assert_eq!(encode(&content, encoding), Some(bytes));
+		assert_eq!(encode("\u{80}", Encoding::Windows1252), None);
+		assert_eq!(encode("→", Encoding::Windows1252), None);
+		assert_eq!(Encoding::from_name("Latin1"), Some(Encoding::Windows1252));
+	}
+}
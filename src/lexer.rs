@@ -0,0 +1,408 @@
+const KEYWORDS: [&str; 44] = [
+	"auto", "break", "case", "char", "const", "continue", "default", "do",
+	"double", "else", "enum", "extern", "float", "for", "goto", "if",
+	"inline", "int", "long", "register", "restrict", "return", "short", "signed",
+	"sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+	"volatile", "while", "_Alignas", "_Alignof", "_Atomic", "_Bool", "_Complex", "_Generic",
+	"_Imaginary", "_Noreturn", "_Static_assert", "_Thread_local"
+];
+
+//Sorted by length so the longest punctuator is always matched first.
+const PUNCTUATORS: [&str; 23] = [
+	"...", "<<=", ">>=",
+	"->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+	"*=", "/=", "%=", "+=", "-=", "&=", "^=", "|=", "##"
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+	Identifier,
+	Keyword,
+	Number,
+	String,
+	Character,
+	HeaderName,//<file.h> in an #include directive.
+	Punctuator,
+	Comment,
+	Preprocessor//'#' followed by the directive name, e.g. "#  include".
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+	pub kind: TokenKind,
+	pub text: &'a str,
+	pub line: usize,//Starting at 1.
+	pub column: usize,//Byte offset from the start of the line, starting at 0.
+	pub offset: usize,//Byte offset from the start of the content.
+	pub preprocessor: bool//Part of a preprocessor directive (including its head).
+}
+
+impl<'a> Token<'a> {
+	pub fn is_keyword(&self, keyword: &str) -> bool {
+		self.kind == TokenKind::Keyword && self.text == keyword
+	}
+
+	pub fn is_punctuator(&self, punctuator: &str) -> bool {
+		self.kind == TokenKind::Punctuator && self.text == punctuator
+	}
+
+	pub fn is_code(&self) -> bool {
+		self.kind != TokenKind::Comment
+	}
+
+	//Offset of the first byte after the token.
+	pub fn end(&self) -> usize {
+		self.offset + self.text.len()
+	}
+
+	pub fn end_line(&self) -> usize {
+		self.line + self.text.matches('\n').count()
+	}
+
+	//Name of the directive for a preprocessor token ("include" for "#  include").
+	pub fn directive(&self) -> &'a str {
+		if self.kind == TokenKind::Preprocessor {
+			self.text[1..].trim_start()
+		}
+		else {
+			""
+		}
+	}
+}
+
+
+pub fn is_keyword(word: &str) -> bool {
+	KEYWORDS.contains(&word)
+}
+
+pub fn tokenize(content: &str) -> Vec<Token<'_>> {
+	let mut lexer = Lexer {
+		content,
+		bytes: content.as_bytes(),
+		position: 0,
+		line: 1,
+		line_start: 0,
+		in_directive: false,
+		at_line_start: true,
+		directive_name: "",
+		tokens: Vec::new()
+	};
+
+	lexer.run();
+
+	return lexer.tokens;
+}
+
+//Index of the token closing the group opened at tokens[open], if any.
+pub fn matching_close(tokens: &[Token], open: usize) -> Option<usize> {
+	let (opening, closing) = match tokens[open].text {
+		"(" => ("(", ")"),
+		"[" => ("[", "]"),
+		"{" => ("{", "}"),
+		_ => return None
+	};
+
+	let mut depth = 0;
+	for (index, token) in tokens.iter().enumerate().skip(open) {
+		if token.is_punctuator(opening) {
+			depth += 1;
+		}
+		else if token.is_punctuator(closing) {
+			depth -= 1;
+			if depth == 0 {
+				return Some(index);
+			}
+		}
+	}
+
+	return None;
+}
+
+//Index of the opening token matching the closing one at the given index.
+pub fn matching_open(tokens: &[Token], close: usize) -> Option<usize> {
+	let (opening, closing) = match tokens[close].text {
+		")" => ("(", ")"),
+		"]" => ("[", "]"),
+		"}" => ("{", "}"),
+		_ => return None
+	};
+
+	let mut depth = 0;
+	for index in (0..=close).rev() {
+		if tokens[index].is_punctuator(closing) {
+			depth += 1;
+		}
+		else if tokens[index].is_punctuator(opening) {
+			depth -= 1;
+			if depth == 0 {
+				return Some(index);
+			}
+		}
+	}
+
+	return None;
+}
+
+
+struct Lexer<'a> {
+	content: &'a str,
+	bytes: &'a [u8],
+	position: usize,
+	line: usize,
+	line_start: usize,
+	in_directive: bool,
+	at_line_start: bool,//Only whitespace have been seen on the current line.
+	directive_name: &'a str,
+	tokens: Vec<Token<'a>>
+}
+
+impl<'a> Lexer<'a> {
+	fn run(&mut self) {
+		while self.position < self.bytes.len() {
+			let c = self.bytes[self.position];
+
+			if c == b'\n' {
+				self.newline(self.position + 1);
+				self.in_directive = false;
+			}
+			else if c == b'\\' && self.is_line_continuation() {
+				let end = self.position + 1 + self.bytes[self.position + 1..].iter().position(|b| *b == b'\n').unwrap();
+				let at_line_start = self.at_line_start;
+				self.newline(end + 1);
+				self.at_line_start = at_line_start;
+			}
+			else if c.is_ascii_whitespace() {
+				self.position += 1;
+			}
+			else if c == b'#' && self.at_line_start {
+				self.directive();
+			}
+			else {
+				self.at_line_start = false;
+				self.token();
+			}
+		}
+	}
+
+	fn newline(&mut self, next_position: usize) {
+		self.position = next_position;
+		self.line += 1;
+		self.line_start = next_position;
+		self.at_line_start = true;
+	}
+
+	//A backslash only followed by whitespaces up to the end of the line.
+	fn is_line_continuation(&self) -> bool {
+		self.bytes[self.position + 1..].iter()
+			.take_while(|b| **b != b'\n')
+			.all(|b| b.is_ascii_whitespace())
+			&& self.bytes[self.position + 1..].contains(&b'\n')
+	}
+
+	fn directive(&mut self) {
+		let start = self.position;
+		let mut end = start + 1;
+		while end < self.bytes.len() && (self.bytes[end] == b' ' || self.bytes[end] == b'\t') {
+			end += 1;
+		}
+		let name_start = end;
+		while end < self.bytes.len() && is_identifier_char(self.bytes[end]) {
+			end += 1;
+		}
+		if name_start == end {
+			end = start + 1;//Null directive, or '#' followed by something else.
+		}
+
+		self.in_directive = true;
+		self.at_line_start = false;
+		self.directive_name = &self.content[name_start.min(end)..end];
+		self.push(TokenKind::Preprocessor, start, end);
+	}
+
+	fn token(&mut self) {
+		let start = self.position;
+		let c = self.bytes[start];
+		let next = self.bytes.get(start + 1).cloned().unwrap_or(0);
+
+		if c == b'/' && next == b'/' {
+			let end = self.line_end(start);
+			self.push(TokenKind::Comment, start, end);
+		}
+		else if c == b'/' && next == b'*' {
+			let end = match self.content[start + 2..].find("*/") {
+				Some(index) => start + 2 + index + 2,
+				None => self.bytes.len()
+			};
+			self.push(TokenKind::Comment, start, end);
+		}
+		else if c == b'"' {
+			let end = self.quoted(start, b'"');
+			self.push(TokenKind::String, start, end);
+		}
+		else if c == b'\'' {
+			let end = self.quoted(start, b'\'');
+			self.push(TokenKind::Character, start, end);
+		}
+		else if c == b'<' && self.in_directive && self.directive_name == "include" &&
+			self.tokens.last().is_some_and(|t| t.kind == TokenKind::Preprocessor) {
+			let end = match self.bytes[start..self.line_end(start)].iter().position(|b| *b == b'>') {
+				Some(index) => start + index + 1,
+				None => self.line_end(start)
+			};
+			self.push(TokenKind::HeaderName, start, end);
+		}
+		else if c.is_ascii_digit() || (c == b'.' && next.is_ascii_digit()) {
+			let mut end = start + 1;
+			while end < self.bytes.len() {
+				let b = self.bytes[end];
+				let is_exponent_sign = (b == b'+' || b == b'-') && b"eEpP".contains(&self.bytes[end - 1]);
+				if is_exponent_sign || is_identifier_char(b) || b == b'.' {
+					end += 1;
+				}
+				else {
+					break;
+				}
+			}
+			self.push(TokenKind::Number, start, end);
+		}
+		else if is_identifier_char(c) {
+			let mut end = start + 1;
+			while end < self.bytes.len() && is_identifier_char(self.bytes[end]) {
+				end += 1;
+			}
+
+			if end < self.bytes.len() && (self.bytes[end] == b'"' || self.bytes[end] == b'\'') &&
+				["L", "u", "U", "u8"].contains(&&self.content[start..end]) {
+				//Prefixed string or character literal.
+				let quote = self.bytes[end];
+				let end = self.quoted(end, quote);
+				let kind = if quote == b'"' { TokenKind::String } else { TokenKind::Character };
+				self.push(kind, start, end);
+			}
+			else if is_keyword(&self.content[start..end]) {
+				self.push(TokenKind::Keyword, start, end);
+			}
+			else {
+				self.push(TokenKind::Identifier, start, end);
+			}
+		}
+		else if let Some(punctuator) = PUNCTUATORS.iter().find(|p| self.content[start..].starts_with(**p)) {
+			self.push(TokenKind::Punctuator, start, start + punctuator.len());
+		}
+		else {
+			let length = self.content[start..].chars().next().map_or(1, |c| c.len_utf8());
+			self.push(TokenKind::Punctuator, start, start + length);
+		}
+	}
+
+	//End offset of a string or character literal. Unterminated literals stop at the end of the line.
+	fn quoted(&self, start: usize, quote: u8) -> usize {
+		let mut end = start + 1;
+		while end < self.bytes.len() {
+			match self.bytes[end] {
+				b'\\' if end + 1 < self.bytes.len() => end += 2,
+				b'\n' => return end,
+				b if b == quote => return end + 1,
+				_ => end += 1
+			}
+		}
+
+		return self.bytes.len();
+	}
+
+	fn line_end(&self, start: usize) -> usize {
+		match self.bytes[start..].iter().position(|b| *b == b'\n') {
+			Some(index) if index > 0 && self.bytes[start + index - 1] == b'\r' => start + index - 1,
+			Some(index) => start + index,
+			None => self.bytes.len()
+		}
+	}
+
+	fn push(&mut self, kind: TokenKind, start: usize, end: usize) {
+		let end = end.max(start + 1).min(self.bytes.len());
+		self.tokens.push(Token {
+			kind,
+			text: &self.content[start..end],
+			line: self.line,
+			column: start - self.line_start,
+			offset: start,
+			preprocessor: self.in_directive
+		});
+
+		//Tokens spanning over multiple lines (comments, unterminated literals).
+		for (index, b) in self.bytes[start..end].iter().enumerate() {
+			if *b == b'\n' {
+				self.line += 1;
+				self.line_start = start + index + 1;
+			}
+		}
+		self.position = end;
+	}
+}
+
+fn is_identifier_char(c: u8) -> bool {
+	c.is_ascii_alphanumeric() || c == b'_'
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn tokenize_kinds() {
+		let kinds: Vec<TokenKind> = tokenize("int gotoxy = 'a' + \"}\"; // }\n").iter().map(|t| t.kind).collect();
+
+		assert_eq!(kinds, vec![TokenKind::Keyword, TokenKind::Identifier, TokenKind::Punctuator, TokenKind::Character,
+			TokenKind::Punctuator, TokenKind::String, TokenKind::Punctuator, TokenKind::Comment]);
+	}
+
+	#[test]
+	fn tokenize_positions() {
+		let tokens = tokenize("a\n  /* b\n*/ c\n");
+
+		assert_eq!((tokens[0].line, tokens[0].column), (1, 0));
+		assert_eq!((tokens[1].line, tokens[1].column), (2, 2));
+		assert_eq!(tokens[1].end_line(), 3);
+		assert_eq!((tokens[2].line, tokens[2].column), (3, 3));
+	}
+
+	#[test]
+	fn tokenize_punctuators() {
+		let texts: Vec<&str> = tokenize("a->b<<=c...d++").iter().map(|t| t.text).collect();
+
+		assert_eq!(texts, vec!["a", "->", "b", "<<=", "c", "...", "d", "++"]);
+	}
+
+	#[test]
+	fn tokenize_numbers() {
+		let texts: Vec<&str> = tokenize("1.5e-3+0x1F").iter().map(|t| t.text).collect();
+
+		assert_eq!(texts, vec!["1.5e-3", "+", "0x1F"]);
+	}
+
+	#[test]
+	fn tokenize_preprocessor() {
+		let tokens = tokenize("#  include <stdio.h>\n#define A(B) \\\n    B\nA(1) # 2\n");
+
+		assert_eq!(tokens[0].kind, TokenKind::Preprocessor);
+		assert_eq!(tokens[0].directive(), "include");
+		assert_eq!(tokens[1].kind, TokenKind::HeaderName);
+		assert_eq!(tokens[2].directive(), "define");
+		assert!(tokens[3..8].iter().all(|t| t.preprocessor));
+		assert_eq!(tokens[7].line, 3);
+		assert!(tokens[8..].iter().all(|t| !t.preprocessor));
+		assert!(tokens[12].is_punctuator("#"));
+	}
+
+	#[test]
+	fn matching_close_nested() {
+		let tokens = tokenize("f(a, (b), c) + 1");
+
+		assert_eq!(matching_close(&tokens, 1), Some(9));
+		assert_eq!(matching_close(&tokens, 4), Some(6));
+		assert_eq!(matching_close(&tokens, 0), None);
+		assert_eq!(matching_open(&tokens, 9), Some(1));
+		assert_eq!(matching_open(&tokens, 6), Some(4));
+	}
+}
@@ -1,93 +1,234 @@
+#![allow(clippy::needless_return, clippy::vec_init_then_push)]
+
+use std::any::Any;
 use std::env;
-use std::fs::File;
-use std::io::Read;
+use std::fs;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
+use std::process;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
+mod config;
+mod diagnostic;
+mod diff;
+mod fix;
+mod ignore;
+mod input;
+mod lexer;
+mod options;
+mod output;
+mod parser;
 mod rules;
+mod source;
+mod suppression;
+mod walk;
+use config::Config;
+use diagnostic::{Diagnostic, Severity};
+use input::{ENCODING_ID, IO_ERROR_ID};
+use options::Options;
+use output::{Report, Summary};
 use rules::Rule;
+use source::{FileKind, FileKinds, SourceFile};
+use walk::Walker;
+
+//Exit codes, documented in the usage.
+const CLEAN: i32 = 0;
+const VIOLATIONS: i32 = 1;
+const USAGE_ERROR: i32 = 2;
+const IO_ERROR: i32 = 3;
+
+//Reported when checking a file failed because of a bug, the other files are still checked.
+const INTERNAL_ERROR_ID: &str = "InternalError";
 
 fn main() {
-	let mut rules = Vec::<Box<Rule>>::new();
-	//indentation.rs
-	rules.push(Box::new(rules::LineSize::new(80)));
-	rules.push(Box::new(rules::SpaceIndentation::new()));
-	rules.push(Box::new(rules::TrailingWhiteSpace::new()));
-	rules.push(Box::new(rules::IndentationLevel::new(4)));
-	rules.push(Box::new(rules::Comma::new()));
-	rules.push(Box::new(rules::StructureFieldsIndentation::new()));
-
-	//naming.rs
-	rules.push(Box::new(rules::Typedef::new()));
-	rules.push(Box::new(rules::Global::new()));
-
-	//misc.rs
-	rules.push(Box::new(rules::OwnLineBrace::new()));
-	rules.push(Box::new(rules::MultiLinesComment::new()));
-	rules.push(Box::new(rules::Goto::new()));
-	rules.push(Box::new(rules::Enum::new()));
-	rules.push(Box::new(rules::Semicolon::new()));
-	rules.push(Box::new(rules::StaticVariable::new()));
-
-	//preprocessor.rs
-	rules.push(Box::new(rules::PreprocessorOnFirstColumn::new()));
-	rules.push(Box::new(rules::PreprocessorIndentation::new()));
-	rules.push(Box::new(rules::PreprocessorComment::new()));
-	rules.push(Box::new(rules::MultiLinesMacro::new()));
-	rules.push(Box::new(rules::MacroName::new()));
-	rules.push(Box::new(rules::MacroArguments::new()));
-	rules.push(Box::new(rules::IncludePreprocessor::new()));
-	rules.push(Box::new(rules::IncludeOrder::new()));
-	rules.push(Box::new(rules::HeaderGuard::new()));
-
-	//function.rs
-	rules.push(Box::new(rules::FunctionMaxCodeLines::new(25)));
-	rules.push(Box::new(rules::FunctionMaxArguments::new(4)));
-	rules.push(Box::new(rules::FunctionBlankLines::new()));
-	rules.push(Box::new(rules::FunctionStartParenthesis::new()));
-	rules.push(Box::new(rules::MaxFunctionsPerSourceFile::new(10)));
-	rules.push(Box::new(rules::MaxExportedFunctions::new(5)));
-	rules.push(Box::new(rules::FunctionParametersIndentation::new()));
-	rules.push(Box::new(rules::FunctionsPrototypeLocation::new()));	
-
-	//control_structures.rs
-	rules.push(Box::new(rules::ControlStructuresIndentation::new()));
-	rules.push(Box::new(rules::SpecialControlStructuresIndentation::new()));
-	rules.push(Box::new(rules::SwitchDefaultCase::new()));
-	rules.push(Box::new(rules::SwitchEnum::new()));
-	rules.push(Box::new(rules::SwitchEnd::new()));	
-
-	let mut filenames: Vec<String> = Vec::new();
-	for arg in env::args().skip(1) {
-		add_file_or_directory(&mut filenames, &arg);
+	let arguments: Result<Vec<String>, _> = env::args_os().skip(1).map(|a| a.into_string()).collect();
+	let options = match arguments.map_err(|a| format!("Argument '{}' isn't valid UTF-8.", a.to_string_lossy())).and_then(Options::parse) {
+		Ok(options) => options,
+		Err(error) => {
+			eprintln!("{}\n\n{}", error, options::USAGE);
+			process::exit(exit_status(None, false, false));
+		}
+	};
+	if options.help {
+		println!("{}", options::USAGE);
+		return;
 	}
 
-	verify(&rules, &filenames);
-}
+	//The configuration is looked for from the first checked path upwards.
+	let start = options.paths.first().map_or(Path::new("."), |a| Path::new(a));
+	let path = config::discover(start);
+	let config = path.as_ref().map_or(Ok(Config::default()), |p| Config::load(p));
+	let (rules, mut walker) = match config.and_then(|c| Ok((rules::from_config(&c)?, Walker::from_config(&c)?))) {
+		Ok(loaded) => loaded,
+		Err(error) => {
+			eprintln!("{}: {}", path.unwrap_or_default().display(), error);
+			process::exit(exit_status(None, false, false));
+		}
+	};
+
+	walker.excludes = options.excludes.clone();
+	walker.gitignore |= options.gitignore;
+	walker.follow_symlinks = options.follow_symlinks;
+	walker.max_depth = options.max_depth;
 
-fn add_file_or_directory(mut filenames: &mut Vec<String>, pathname: &str) {
-	let path = Path::new(pathname);
-	if path.is_file() {
-		filenames.push(String::from(pathname));
+	//Paths that can't be read are printed first, the files found are still checked.
+	let walk = walker.walk(&options.paths);
+	for message in walk.errors.iter().chain(walk.warnings.iter()) {
+		eprintln!("{}", message);
 	}
-	else if path.is_dir() {
-		for entry in path.read_dir().expect(&format!("Something went wrong opening {}", pathname)) {
-    		if let Ok(entry) = entry {
-        		add_file_or_directory(&mut filenames, entry.path().to_str().unwrap());
-    		}
+
+	let reports = verify(&rules, &walker.kinds, &walk.filenames, &options);
+	if options.diff {
+		//Only the patch is printed, so that it can be given to 'patch -p0'. The files that couldn't be checked are told apart.
+		//The patch is in the encoding of each file, like the files that '--fix' writes.
+		let mut stdout = io::stdout().lock();
+		let written = reports.iter().try_for_each(|report| stdout.write_all(&report.diff)).and_then(|_| stdout.flush());
+		if written.is_err() {
+			process::exit(IO_ERROR);
 		}
+		for diagnostic in reports.iter().flat_map(|r| r.diagnostics.iter()) {
+			eprintln!("{}", output::gcc::line(diagnostic));
+		}
+	}
+	else {
+		let summary = Summary { skipped: walk.skipped, ..Summary::new(&reports) };
+		print!("{}", options.format.render(&reports, &summary, &rules, options.color.enabled()));
 	}
+
+	//What kept the rules from checking as much as they could, such as the headers that couldn't be read.
+	for message in rules.iter().flat_map(|rule| rule.warnings()) {
+		eprintln!("{}", message);
+	}
+	if walk.skipped > 0 && (options.diff || !options.format.has_summary()) {
+		eprintln!("norme: skipped {} files", walk.skipped);
+	}
+
+	process::exit(exit_status(Some(&reports), !walk.errors.is_empty(), options.diff));
 }
 
-fn verify(rules: &Vec<Box<Rule>>, filenames: &[String]) {
-	for filename in filenames {
-		let mut file = File::open(&filename).expect("file not found");
-		let mut content = String::new();
-		file.read_to_string(&mut content).expect(&format!("Something went wrong reading {}", filename));
+//Exit code of the run, None when it couldn't start because of the command line or the configuration. Files that couldn't
+//be read, written or checked take precedence over the errors found in the others.
+fn exit_status(reports: Option<&[Report]>, walk_failed: bool, diff: bool) -> i32 {
+	let reports = match reports {
+		Some(reports) => reports,
+		None => return USAGE_ERROR
+	};
+	let diagnostics = || reports.iter().flat_map(|r| r.diagnostics.iter());
+
+	if walk_failed || diagnostics().any(|d| d.rule == IO_ERROR_ID || d.rule == INTERNAL_ERROR_ID) {
+		return IO_ERROR;
+	}
+	let violations = diagnostics().any(|d| d.severity == Severity::Error) || (diff && reports.iter().any(|r| !r.diff.is_empty()));
+	return if violations { VIOLATIONS } else { CLEAN };
+}
+
+//Files are shared between the jobs, the reports are returned in the order of the files.
+fn verify(rules: &[Box<dyn Rule>], kinds: &FileKinds, filenames: &[String], options: &Options) -> Vec<Report> {
+	let next = AtomicUsize::new(0);
+	let results = Mutex::new(vec![Report::default(); filenames.len()]);
+
+	thread::scope(|scope| {
+		for _ in 0..options.jobs.min(filenames.len()) {
+			scope.spawn(|| {
+				loop {
+					let index = next.fetch_add(1, Ordering::Relaxed);
+					let filename = match filenames.get(index) {
+						Some(filename) => filename,
+						None => break
+					};
 
-		for rule in rules.iter() {
-			for error in rule.verify(&filename, &content).iter() {
-				println!("{}", error);
+					let report = panic::catch_unwind(AssertUnwindSafe(|| verify_file(rules, filename, kinds.kind(filename), options)))
+						.unwrap_or_else(|error| internal_error(filename, error));
+					results.lock().unwrap()[index] = report;
+				}
+			});
+		}
+	});
+
+	return results.into_inner().unwrap();
+}
+
+//Report of a file whose check panicked, with the panic message.
+fn internal_error(filename: &str, error: Box<dyn Any + Send>) -> Report {
+	let message = error.downcast_ref::<&str>().map(|m| String::from(*m)).or_else(|| error.downcast_ref::<String>().cloned()).unwrap_or_default();
+	let diagnostic = Diagnostic::file(INTERNAL_ERROR_ID, filename, format!("Internal error while checking the file: {}.", message));
+	return Report { filename: String::from(filename), diagnostics: vec![diagnostic], ..Report::default() };
+}
+
+//Diagnostics remaining on the file, once fixed when asked to. A file that can't be read, decoded or written is reported in them.
+fn verify_file(rules: &[Box<dyn Rule>], filename: &str, kind: FileKind, options: &Options) -> Report {
+	let mut report = Report { filename: String::from(filename), ..Report::default() };
+	let bytes = match fs::read(filename) {
+		Ok(bytes) => bytes,
+		Err(error) => {
+			report.diagnostics.push(Diagnostic::file(IO_ERROR_ID, filename, format!("Can't read the file: {}.", error)));
+			return report;
+		}
+	};
+	let (mut content, encoding) = match input::decode(filename, &bytes, options.encoding) {
+		Ok(decoded) => decoded,
+		Err(diagnostic) => {
+			report.diagnostics.push(*diagnostic);
+			report.content = String::from_utf8_lossy(&bytes).into_owned();
+			return report;
+		}
+	};
+
+	if options.diff {
+		let patch = diff::unified(filename, &content, &fix::fix(rules, filename, kind, &content));
+		match input::encode(&patch, encoding) {
+			Some(bytes) => report.diff = bytes,
+			None => report.diagnostics.push(Diagnostic::file(ENCODING_ID, filename, "The corrections can't be represented in the encoding of the file."))
+		}
+		return report;
+	}
+	let mut errors = Vec::new();
+	if options.fix {
+		let fixed = fix::fix(rules, filename, kind, &content);
+		if fixed != content {
+			//Written back in the encoding it was read with.
+			let written = match input::encode(&fixed, encoding) {
+				Some(bytes) => fs::write(filename, bytes).map_err(|error| error.to_string()),
+				None => Err(String::from("a correction can't be represented in the encoding of the file"))
+			};
+			match written {
+				Ok(()) => content = fixed,
+				Err(error) => errors.push(Diagnostic::file(IO_ERROR_ID, filename, format!("Can't write the corrections: {}.", error)))
 			}
 		}
 	}
+
+	report.diagnostics = errors;
+	report.diagnostics.extend(rules::check_all(rules, &SourceFile::new(filename, kind, &content)));
+	report.content = content;
+	return report;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn exit_statuses() {
+		let report = |diagnostics: Vec<Diagnostic>| Report { filename: String::from("a.c"), diagnostics, ..Report::default() };
+		let mut warning = Diagnostic::new(suppression::UNUSED_ID, "a.c", 1, "Unused.");
+		warning.severity = Severity::Warning;
+		let error = Diagnostic::new("Goto", "a.c", 2, "Goto used.");
+		let io_error = Diagnostic::file(IO_ERROR_ID, "b.c", "Can't read the file.");
+
+		assert_eq!(exit_status(Some(&[]), false, false), CLEAN);
+		assert_eq!(exit_status(Some(&[report(vec![warning])]), false, false), CLEAN);
+		assert_eq!(exit_status(Some(&[report(vec![error.clone()])]), false, false), VIOLATIONS);
+		assert_eq!(exit_status(None, false, false), USAGE_ERROR);
+		assert_eq!(exit_status(Some(&[report(vec![error.clone()]), report(vec![io_error])]), false, false), IO_ERROR);
+		assert_eq!(exit_status(Some(&[report(vec![error])]), true, false), IO_ERROR);
+
+		let changed = [Report { diff: b"--- a.c".to_vec(), ..report(Vec::new()) }];
+		assert_eq!(exit_status(Some(&changed), false, true), VIOLATIONS);
+		assert_eq!(exit_status(Some(&changed), false, false), CLEAN);
+		assert_eq!(exit_status(Some(&[report(vec![Diagnostic::file(INTERNAL_ERROR_ID, "a.c", "Panicked.")])]), false, true), IO_ERROR);
+	}
 }
@@ -0,0 +1,210 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::thread;
+
+use input::Encoding;
+use output::Format;
+
+pub const USAGE: &str = "Usage: norme [OPTIONS] [PATH]...
+
+Checks the C files found in the given files and directories.
+
+Options:
+  -j, --jobs <N>         Number of files checked concurrently (default: number of CPUs).
+  -f, --format <NAME>    Output format: gcc, human, classic, json, ndjson, sarif, junit
+                         or checkstyle (default: gcc).
+      --color <WHEN>     Colours of the human format: auto, always or never (default: auto).
+      --encoding <NAME>  Encoding of the files that aren't valid UTF-8: utf-8 to report them,
+                         or windows-1252 (also latin1) to check them anyway (default: utf-8).
+      --exclude <GLOB>   Skip the matching files and directories, with the syntax of the
+                         '.normeignore' files. Can be repeated.
+      --gitignore        Skip what the '.gitignore' files list too.
+      --follow-symlinks  Follow the symbolic links found in directories.
+      --max-depth <N>    Don't look for files deeper than N directories in the given ones.
+      --fix              Correct the files in place where it can be done automatically.
+      --diff             Print the corrections as a unified diff instead, without writing them.
+  -h, --help             Print this help.
+
+Exit status:
+  0  No error found, warnings alone don't fail.
+  1  Errors found, or with '--diff' files that would be changed.
+  2  Invalid usage or configuration.
+  3  A file or directory couldn't be read or written, or a file couldn't be checked.";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+	Auto,
+	Always,
+	Never
+}
+
+impl Color {
+	//With 'auto', only when printing to a terminal and NO_COLOR isn't set.
+	pub fn enabled(&self) -> bool {
+		match *self {
+			Color::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+			Color::Always => true,
+			Color::Never => false
+		}
+	}
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+	pub help: bool,
+	pub jobs: usize,
+	pub format: Format,
+	pub color: Color,
+	pub encoding: Encoding,
+	pub excludes: Vec<String>,
+	pub gitignore: bool,
+	pub follow_symlinks: bool,
+	pub max_depth: Option<usize>,
+	pub fix: bool,
+	pub diff: bool,
+	pub paths: Vec<String>
+}
+
+impl Options {
+	pub fn parse<I: IntoIterator<Item = String>>(arguments: I) -> Result<Options, String> {
+		let mut options = Options {
+			help: false,
+			jobs: thread::available_parallelism().map_or(1, |n| n.get()),
+			format: Format::Gcc,
+			color: Color::Auto,
+			encoding: Encoding::Utf8,
+			excludes: Vec::new(),
+			gitignore: false,
+			follow_symlinks: false,
+			max_depth: None,
+			fix: false,
+			diff: false,
+			paths: Vec::new()
+		};
+
+		let mut arguments = arguments.into_iter();
+		while let Some(argument) = arguments.next() {
+			//"--option=value" is the same as "--option value", and "-j4" as "-j 4".
+			let (name, mut value) = match argument.find('=') {
+				Some(index) if argument.starts_with("--") => (String::from(&argument[..index]), Some(String::from(&argument[index + 1..]))),
+				_ if (argument.starts_with("-j") || argument.starts_with("-f")) && argument.len() > 2 => (String::from(&argument[..2]), Some(String::from(&argument[2..]))),
+				_ => (argument.clone(), None)
+			};
+			let mut take_value = || value.take().or_else(|| arguments.next()).ok_or(format!("Missing value for '{}'.", name));
+
+			match name.as_str() {
+				"-h" | "--help" => options.help = true,
+				"--fix" => options.fix = true,
+				"--gitignore" => options.gitignore = true,
+				"--exclude" => options.excludes.push(take_value()?),
+				"--follow-symlinks" => options.follow_symlinks = true,
+				"--max-depth" => {
+					let depth = take_value()?;
+					options.max_depth = Some(depth.parse::<usize>().map_err(|_| format!("Invalid depth '{}'.", depth))?);
+				},
+				"--diff" => options.diff = true,
+				"-j" | "--jobs" => {
+					let jobs = take_value()?;
+					options.jobs = match jobs.parse::<usize>() {
+						Ok(jobs) if jobs > 0 => jobs,
+						_ => return Err(format!("Invalid number of jobs '{}'.", jobs))
+					};
+				},
+				"-f" | "--format" => {
+					let format = take_value()?;
+					options.format = Format::from_name(&format)
+						.ok_or(format!("Unknown format '{}', expected one of: {}.", format, Format::NAMES.join(", ")))?;
+				},
+				"--color" => {
+					options.color = match take_value()?.as_str() {
+						"auto" => Color::Auto,
+						"always" => Color::Always,
+						"never" => Color::Never,
+						when => return Err(format!("Unknown color mode '{}', expected one of: auto, always, never.", when))
+					};
+				},
+				"--encoding" => {
+					let encoding = take_value()?;
+					options.encoding = Encoding::from_name(&encoding)
+						.ok_or(format!("Unknown encoding '{}', expected utf-8 or windows-1252.", encoding))?;
+				},
+				"--" => {
+					options.paths.extend(arguments);
+					break;
+				},
+				_ if name.starts_with('-') && name.len() > 1 => return Err(format!("Unknown option '{}'.", name)),
+				_ => options.paths.push(argument.clone())
+			}
+			if let Some(value) = value {
+				return Err(format!("Unexpected value '{}' for '{}'.", value, name));
+			}
+		}
+
+		if options.fix && options.diff {
+			return Err(String::from("'--fix' and '--diff' can't be used together."));
+		}
+		return Ok(options);
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(arguments: &[&str]) -> Result<Options, String> {
+		Options::parse(arguments.iter().map(|a| String::from(*a)))
+	}
+
+	#[test]
+	fn jobs() {
+		assert_eq!(parse(&["-j", "3", "src"]).unwrap().jobs, 3);
+		assert_eq!(parse(&["-j1", "src"]).unwrap().jobs, 1);
+		assert_eq!(parse(&["--jobs=2"]).unwrap().jobs, 2);
+		assert_eq!(parse(&["src", "--jobs", "1"]).unwrap().paths, ["src"]);
+		assert!(parse(&[]).unwrap().jobs >= 1);
+
+		assert!(parse(&["--jobs"]).is_err());
+		assert!(parse(&["--jobs", "0"]).is_err());
+		assert!(parse(&["-j", "many"]).is_err());
+	}
+
+	#[test]
+	fn format() {
+		assert_eq!(parse(&[]).unwrap().format, Format::Gcc);
+		assert_eq!(parse(&["--format=classic"]).unwrap().format, Format::Classic);
+		assert_eq!(parse(&["--format", "json"]).unwrap().format, Format::Json);
+		assert_eq!(parse(&["-f", "ndjson"]).unwrap().format, Format::Ndjson);
+		assert_eq!(parse(&["-fjson"]).unwrap().format, Format::Json);
+		assert!(parse(&["--format=xml"]).is_err());
+
+		assert_eq!(parse(&["--encoding", "latin1"]).unwrap().encoding, Encoding::Windows1252);
+		assert!(parse(&["--encoding", "ebcdic"]).is_err());
+
+		assert_eq!(parse(&[]).unwrap().color, Color::Auto);
+		assert_eq!(parse(&["--color=never", "-f", "human"]).unwrap().color, Color::Never);
+		assert!(!Color::Never.enabled());
+		assert!(parse(&["--color", "sometimes"]).is_err());
+	}
+
+	#[test]
+	fn paths() {
+		assert_eq!(parse(&["a.c", "dir"]).unwrap().paths, ["a.c", "dir"]);
+		assert_eq!(parse(&["--", "-weird.c"]).unwrap().paths, ["-weird.c"]);
+		assert_eq!(parse(&["-"]).unwrap().paths, ["-"]);
+
+		assert!(parse(&["--unknown"]).is_err());
+		assert!(parse(&["--help=yes"]).is_err());
+		assert!(parse(&["--help"]).unwrap().help);
+		assert!(parse(&["--fix", "src"]).unwrap().fix);
+		assert!(parse(&["--diff", "src"]).unwrap().diff);
+		assert!(parse(&["--diff", "--fix"]).is_err());
+		assert_eq!(parse(&["--exclude", "vendor/", "--exclude=*.gen.c", "src"]).unwrap().excludes, ["vendor/", "*.gen.c"]);
+		assert!(parse(&["--gitignore"]).unwrap().gitignore);
+		assert!(parse(&["--follow-symlinks"]).unwrap().follow_symlinks);
+		assert_eq!(parse(&["--max-depth=2"]).unwrap().max_depth, Some(2));
+		assert!(parse(&["--max-depth", "-1"]).is_err());
+	}
+}
@@ -0,0 +1,15 @@
+use output::Report;
+
+//"[file:line]message", or "[file]message" for diagnostics concerning the whole file.
+pub fn render(reports: &[Report]) -> String {
+	let mut output = String::new();
+
+	for diagnostic in reports.iter().flat_map(|r| r.diagnostics.iter()) {
+		match diagnostic.line {
+			Some(line) => output.push_str(&format!("[{}:{}]{}\n", diagnostic.filename, line, diagnostic.message)),
+			None => output.push_str(&format!("[{}]{}\n", diagnostic.filename, diagnostic.message))
+		}
+	}
+
+	return output;
+}
@@ -0,0 +1,39 @@
+use diagnostic::Diagnostic;
+use output::Report;
+
+//"file:line:column: severity: message [rule]", understood by editors and IDE problem matchers. Columns start at 1.
+pub fn render(reports: &[Report]) -> String {
+	let mut output = String::new();
+
+	for diagnostic in reports.iter().flat_map(|r| r.diagnostics.iter()) {
+		output.push_str(&line(diagnostic));
+		output.push('\n');
+	}
+
+	return output;
+}
+
+pub fn line(diagnostic: &Diagnostic) -> String {
+	let mut location = diagnostic.filename.clone();
+	if let Some(line) = diagnostic.line {
+		location.push_str(&format!(":{}", line));
+		if let Some((start, _)) = diagnostic.columns {
+			location.push_str(&format!(":{}", start + 1));
+		}
+	}
+
+	format!("{}: {}: {} [{}]", location, diagnostic.severity.name(), diagnostic.message, diagnostic.rule)
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn gcc() {
+		assert_eq!(line(&Diagnostic::new("Goto", "a.c", 2, "Goto used.").columns(4, 8)), "a.c:2:5: error: Goto used. [Goto]");
+		assert_eq!(line(&Diagnostic::new("LineSize", "a.c", 3, "Too long.")), "a.c:3: error: Too long. [LineSize]");
+		assert_eq!(line(&Diagnostic::file("HeaderGuard", "b.h", "Missing.")), "b.h: error: Missing. [HeaderGuard]");
+	}
+}
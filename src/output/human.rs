@@ -0,0 +1,141 @@
+use diagnostic::{Diagnostic, Severity};
+use output::{Report, Summary};
+
+//Columns of the tab stops when showing the lines.
+const TAB_SIZE: usize = 8;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+
+//Each diagnostic with the line it was found on, its columns underlined, then how many were found.
+pub fn render(reports: &[Report], summary: &Summary, colors: bool) -> String {
+	let mut output = String::new();
+
+	for report in reports.iter() {
+		let lines: Vec<&str> = report.content.lines().collect();
+		for diagnostic in report.diagnostics.iter() {
+			output.push_str(&snippet(diagnostic, &lines, colors));
+			output.push('\n');
+		}
+	}
+
+	if summary.errors + summary.warnings > 0 {
+		let total = format!("Found {} error(s) and {} warning(s) in {} file(s).", summary.errors, summary.warnings, summary.files_with_diagnostics);
+		output.push_str(&paint(&total, BOLD, colors));
+		output.push('\n');
+	}
+	if summary.skipped > 0 {
+		output.push_str(&format!("Skipped {} file(s) without a checked extension.\n", summary.skipped));
+	}
+
+	return output;
+}
+
+fn snippet(diagnostic: &Diagnostic, lines: &[&str], colors: bool) -> String {
+	let color = match diagnostic.severity {
+		Severity::Error => RED,
+		Severity::Warning => YELLOW
+	};
+	let mut output = format!("{}{}\n", paint(diagnostic.severity.name(), color, colors),
+		paint(&format!(": {} [{}]", diagnostic.message, diagnostic.rule), BOLD, colors));
+
+	let (number, text) = match diagnostic.line {
+		Some(number) => (number, lines.get(number.wrapping_sub(1)).map_or("", |text| text.trim_end_matches('\r'))),
+		None => {
+			output.push_str(&format!("{} {}\n", paint("-->", BLUE, colors), diagnostic.filename));
+			return output;
+		}
+	};
+
+	let gutter = " ".repeat(number.to_string().len());
+	let location = match diagnostic.columns {
+		Some((start, _)) => format!("{}:{}:{}", diagnostic.filename, number, start + 1),
+		None => format!("{}:{}", diagnostic.filename, number)
+	};
+	let (shown, underline) = expand(text, diagnostic.columns);
+
+	output.push_str(&format!("{}{} {}\n", gutter, paint("-->", BLUE, colors), location));
+	output.push_str(&format!("{} {}\n", gutter, paint("|", BLUE, colors)));
+	output.push_str(&format!("{} {} {}\n", paint(&number.to_string(), BLUE, colors), paint("|", BLUE, colors), shown));
+	if let Some((start, width)) = underline {
+		let marks = format!("^{}", "~".repeat(width - 1));
+		output.push_str(&format!("{} {} {}{}\n", gutter, paint("|", BLUE, colors), " ".repeat(start), paint(&marks, color, colors)));
+	}
+
+	return output;
+}
+
+//The line with its tabs expanded, and the display column and width of the byte range, at least one to show a mark.
+fn expand(text: &str, columns: Option<(usize, usize)>) -> (String, Option<(usize, usize)>) {
+	let mut shown = String::with_capacity(text.len());
+	let mut start = None;
+	let mut end = None;
+
+	for (index, c) in text.char_indices() {
+		if let Some((first, last)) = columns {
+			if index >= first && start.is_none() {
+				start = Some(shown.chars().count());
+			}
+			if index >= last && end.is_none() {
+				end = Some(shown.chars().count());
+			}
+		}
+		if c == '\t' {
+			let width = TAB_SIZE - shown.chars().count() % TAB_SIZE;
+			shown.push_str(&" ".repeat(width));
+		}
+		else {
+			shown.push(c);
+		}
+	}
+
+	let length = shown.chars().count();
+	let underline = columns.map(|_| {
+		let start = start.unwrap_or(length);
+		let end = end.unwrap_or(length);
+		(start, end.saturating_sub(start).max(1))
+	});
+
+	return (shown, underline);
+}
+
+fn paint(text: &str, color: &str, colors: bool) -> String {
+	if colors {
+		format!("{}{}{}", color, text, RESET)
+	}
+	else {
+		String::from(text)
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn snippets() {
+		let lines = ["int x;", "\tfoo(a,b);"];
+		assert_eq!(snippet(&Diagnostic::new("Comma", "a.c", 2, "Missing space.").columns(6, 7), &lines, false),
+			"error: Missing space. [Comma]\n --> a.c:2:7\n  |\n2 |         foo(a,b);\n  |              ^\n");
+		assert_eq!(snippet(&Diagnostic::new("LineSize", "a.c", 1, "Too long."), &lines, false),
+			"error: Too long. [LineSize]\n --> a.c:1\n  |\n1 | int x;\n");
+		assert_eq!(snippet(&Diagnostic::file("HeaderGuard", "b.h", "Missing."), &lines, false),
+			"error: Missing. [HeaderGuard]\n--> b.h\n");
+		assert_eq!(snippet(&Diagnostic::new("Goto", "a.c", 1, "Goto.").columns(0, 3), &lines, true),
+			"\x1b[1;31merror\x1b[0m\x1b[1m: Goto. [Goto]\x1b[0m\n \x1b[1;34m-->\x1b[0m a.c:1:1\n  \x1b[1;34m|\x1b[0m\n\
+			\x1b[1;34m1\x1b[0m \x1b[1;34m|\x1b[0m int x;\n  \x1b[1;34m|\x1b[0m \x1b[1;31m^~~\x1b[0m\n");
+	}
+
+	#[test]
+	fn underline() {
+		assert_eq!(expand("a\tb", Some((2, 3))), (String::from("a       b"), Some((8, 1))));
+		assert_eq!(expand("ab  ", Some((2, 4))), (String::from("ab  "), Some((2, 2))));
+		assert_eq!(expand("ab", Some((2, 3))), (String::from("ab"), Some((2, 1))));
+		assert_eq!(expand("é = 1", Some((3, 4))), (String::from("é = 1"), Some((2, 1))));
+	}
+}
@@ -0,0 +1,102 @@
+use diagnostic::Diagnostic;
+use output::{Report, Summary};
+
+//A single document: {"diagnostics": [...], "summary": {...}}.
+pub fn render(reports: &[Report], summary: &Summary) -> String {
+	let diagnostics: Vec<String> = reports.iter().flat_map(|r| r.diagnostics.iter()).map(|d| diagnostic(d, false)).collect();
+
+	format!("{{\"diagnostics\":[{}],\"summary\":{}}}\n", diagnostics.join(","), summary_object(summary, false))
+}
+
+//Newline delimited JSON: an object per diagnostic, then the summary, each with their "type".
+pub fn render_lines(reports: &[Report], summary: &Summary) -> String {
+	let mut output = String::new();
+
+	for d in reports.iter().flat_map(|r| r.diagnostics.iter()) {
+		output.push_str(&diagnostic(d, true));
+		output.push('\n');
+	}
+	output.push_str(&summary_object(summary, true));
+	output.push('\n');
+
+	return output;
+}
+
+//Lines and columns start at 1, the end column is excluded. They are null when the whole file, or line, is concerned.
+fn diagnostic(diagnostic: &Diagnostic, typed: bool) -> String {
+	format!("{{{}\"rule\":{},\"file\":{},\"line\":{},\"column\":{},\"end_column\":{},\"severity\":{},\"message\":{},\"fixable\":{}}}",
+		if typed { "\"type\":\"diagnostic\"," } else { "" },
+		string(diagnostic.rule),
+		string(&diagnostic.filename),
+		optional(diagnostic.line),
+		optional(diagnostic.columns.map(|c| c.0 + 1)),
+		optional(diagnostic.columns.map(|c| c.1 + 1)),
+		string(diagnostic.severity.name()),
+		string(&diagnostic.message),
+		!diagnostic.fix.is_empty())
+}
+
+fn summary_object(summary: &Summary, typed: bool) -> String {
+	format!("{{{}\"files\":{},\"files_with_diagnostics\":{},\"errors\":{},\"warnings\":{},\"fixable\":{},\"skipped\":{}}}",
+		if typed { "\"type\":\"summary\"," } else { "" },
+		summary.files, summary.files_with_diagnostics, summary.errors, summary.warnings, summary.fixable, summary.skipped)
+}
+
+fn optional(value: Option<usize>) -> String {
+	value.map_or(String::from("null"), |v| v.to_string())
+}
+
+pub fn string(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len() + 2);
+	escaped.push('"');
+	for c in text.chars() {
+		match c {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			'\t' => escaped.push_str("\\t"),
+			c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+			c => escaped.push(c)
+		}
+	}
+	escaped.push('"');
+
+	return escaped;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn reports() -> Vec<Report> {
+		vec![
+			Report { filename: String::from("a.c"), diagnostics: vec![Diagnostic::new("Goto", "a.c", 2, "Goto \"used\".").columns(4, 8)], content: String::new(), diff: Vec::new() },
+			Report { filename: String::from("b.h"), diagnostics: vec![Diagnostic::file("HeaderGuard", "b.h", "Missing.").edit(0, 0, "#ifndef")], content: String::new(), diff: Vec::new() }
+		]
+	}
+
+	#[test]
+	fn json() {
+		assert_eq!(render(&reports(), &Summary::new(&reports())), "{\"diagnostics\":[\
+			{\"rule\":\"Goto\",\"file\":\"a.c\",\"line\":2,\"column\":5,\"end_column\":9,\"severity\":\"error\",\"message\":\"Goto \\\"used\\\".\",\"fixable\":false},\
+			{\"rule\":\"HeaderGuard\",\"file\":\"b.h\",\"line\":null,\"column\":null,\"end_column\":null,\"severity\":\"error\",\"message\":\"Missing.\",\"fixable\":true}],\
+			\"summary\":{\"files\":2,\"files_with_diagnostics\":2,\"errors\":2,\"warnings\":0,\"fixable\":1,\"skipped\":0}}\n");
+	}
+
+	#[test]
+	fn lines() {
+		let output = render_lines(&reports(), &Summary::new(&reports()));
+		let lines: Vec<&str> = output.lines().collect();
+
+		assert_eq!(lines.len(), 3);
+		assert!(lines[0].starts_with("{\"type\":\"diagnostic\",\"rule\":\"Goto\","));
+		assert!(lines[2].starts_with("{\"type\":\"summary\",\"files\":2,"));
+	}
+
+	#[test]
+	fn escape() {
+		assert_eq!(string("a\\b\n\t\u{1}é"), "\"a\\\\b\\n\\t\\u0001é\"");
+	}
+}
@@ -0,0 +1,93 @@
+pub mod classic;
+pub mod gcc;
+pub mod human;
+pub mod json;
+pub mod sarif;
+pub mod xml;
+
+use diagnostic::{Diagnostic, Severity};
+use rules::Rule;
+
+//Diagnostics found in one checked file.
+#[derive(Debug, Default, Clone)]
+pub struct Report {
+	pub filename: String,
+	pub diagnostics: Vec<Diagnostic>,
+	pub content: String,//Checked content, once fixed, to show the lines of the diagnostics.
+	pub diff: Vec<u8>//Unified diff of the fixes in the encoding of the file, only computed for '--diff'.
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Gcc,
+	Human,
+	Classic,
+	Json,
+	Ndjson,
+	Sarif,
+	Junit,
+	Checkstyle
+}
+
+impl Format {
+	pub const NAMES: [&'static str; 8] = ["gcc", "human", "classic", "json", "ndjson", "sarif", "junit", "checkstyle"];
+
+	pub fn from_name(name: &str) -> Option<Format> {
+		match name {
+			"gcc" => Some(Format::Gcc),
+			"human" => Some(Format::Human),
+			"classic" => Some(Format::Classic),
+			"json" => Some(Format::Json),
+			"ndjson" => Some(Format::Ndjson),
+			"sarif" => Some(Format::Sarif),
+			"junit" => Some(Format::Junit),
+			"checkstyle" => Some(Format::Checkstyle),
+			_ => None
+		}
+	}
+
+	//Whether the summary of the run, with the number of skipped files, is part of the output.
+	pub fn has_summary(&self) -> bool {
+		matches!(*self, Format::Human | Format::Json | Format::Ndjson)
+	}
+
+	//Whole output of the run, reports being in the order of the files.
+	pub fn render(&self, reports: &[Report], summary: &Summary, rules: &[Box<dyn Rule>], colors: bool) -> String {
+		match *self {
+			Format::Gcc => gcc::render(reports),
+			Format::Human => human::render(reports, summary, colors),
+			Format::Classic => classic::render(reports),
+			Format::Json => json::render(reports, summary),
+			Format::Ndjson => json::render_lines(reports, summary),
+			Format::Sarif => sarif::render(reports, rules),
+			Format::Junit => xml::render_junit(reports, summary),
+			Format::Checkstyle => xml::render_checkstyle(reports)
+		}
+	}
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+	pub files: usize,
+	pub files_with_diagnostics: usize,
+	pub errors: usize,
+	pub warnings: usize,
+	pub fixable: usize,
+	pub skipped: usize//Files found in the directories but not checked, given apart from the reports.
+}
+
+impl Summary {
+	pub fn new(reports: &[Report]) -> Summary {
+		let diagnostics = || reports.iter().flat_map(|r| r.diagnostics.iter());
+		Summary {
+			files: reports.len(),
+			files_with_diagnostics: reports.iter().filter(|r| !r.diagnostics.is_empty()).count(),
+			errors: diagnostics().filter(|d| d.severity == Severity::Error).count(),
+			warnings: diagnostics().filter(|d| d.severity == Severity::Warning).count(),
+			fixable: diagnostics().filter(|d| !d.fix.is_empty()).count(),
+			skipped: 0
+		}
+	}
+}
@@ -0,0 +1,85 @@
+use diagnostic::{Diagnostic, Severity};
+use output::Report;
+use output::json::string;
+use rules::Rule;
+use input;
+use suppression;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+
+//A single run of the "norme" tool, with a descriptor for every checked rule.
+pub fn render(reports: &[Report], rules: &[Box<dyn Rule>]) -> String {
+	let mut descriptors: Vec<(&str, String)> = rules.iter().map(|r| (r.id(), r.description())).collect();
+	descriptors.push((suppression::UNUSED_ID, String::from("Suppression comments must silence at least one diagnostic.")));
+	descriptors.push((input::IO_ERROR_ID, String::from("Files must be readable, and writable to be corrected.")));
+	descriptors.push((input::ENCODING_ID, String::from("Files must be encoded in UTF-8.")));
+
+	let rule_objects: Vec<String> = descriptors.iter().map(|&(id, ref description)| {
+		let level = if id == suppression::UNUSED_ID { Severity::Warning } else { Severity::Error };
+		format!("{{\"id\":{},\"name\":{},\"shortDescription\":{{\"text\":{}}},\"defaultConfiguration\":{{\"level\":{}}}}}",
+			string(id), string(id), string(description), string(level.name()))
+	}).collect();
+
+	let results: Vec<String> = reports.iter().flat_map(|r| r.diagnostics.iter()).map(|diagnostic| {
+		let index = descriptors.iter().position(|&(id, _)| id == diagnostic.rule);
+		result(diagnostic, index)
+	}).collect();
+
+	format!("{{\"$schema\":{},\"version\":\"2.1.0\",\"runs\":[{{\"tool\":{{\"driver\":{{\"name\":\"norme\",\"version\":{},\"rules\":[{}]}}}},\
+		\"results\":[{}]}}]}}\n",
+		string(SCHEMA), string(env!("CARGO_PKG_VERSION")), rule_objects.join(","), results.join(","))
+}
+
+fn result(diagnostic: &Diagnostic, rule_index: Option<usize>) -> String {
+	//Columns start at 1 and the end one is excluded, as for the diagnostics.
+	let mut region = Vec::new();
+	if let Some(line) = diagnostic.line {
+		region.push(format!("\"startLine\":{}", line));
+	}
+	if let Some((start, end)) = diagnostic.columns {
+		region.push(format!("\"startColumn\":{},\"endColumn\":{}", start + 1, end + 1));
+	}
+	let region = if region.is_empty() { String::new() } else { format!(",\"region\":{{{}}}", region.join(",")) };
+
+	format!("{{\"ruleId\":{}{},\"level\":{},\"message\":{{\"text\":{}}},\"locations\":[{{\"physicalLocation\":{{\"artifactLocation\":{{\"uri\":{}}}{}}}}}]}}",
+		string(diagnostic.rule),
+		rule_index.map_or(String::new(), |index| format!(",\"ruleIndex\":{}", index)),
+		string(diagnostic.severity.name()),
+		string(&diagnostic.message),
+		string(&uri(&diagnostic.filename)),
+		region)
+}
+
+//Relative reference to the file, with '/' separators and the characters that are not allowed in an URI percent-encoded.
+fn uri(filename: &str) -> String {
+	let mut uri = String::new();
+	for byte in filename.replace('\\', "/").bytes() {
+		match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+			_ => uri.push_str(&format!("%{:02X}", byte))
+		}
+	}
+
+	return uri;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rules::{Goto, LineSize};
+
+	#[test]
+	fn sarif() {
+		let rules: Vec<Box<dyn Rule>> = vec![Box::new(LineSize::new(80)), Box::new(Goto::new())];
+		let reports = vec![Report { filename: String::from("src/my file.c"), diagnostics: vec![Diagnostic::new("Goto", "src/my file.c", 2, "Goto used.").columns(4, 8)], content: String::new(), diff: Vec::new() }];
+		let output = render(&reports, &rules);
+
+		assert!(output.starts_with("{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"version\":\"2.1.0\","));
+		assert!(output.contains("{\"id\":\"LineSize\",\"name\":\"LineSize\",\"shortDescription\":{\"text\":\"No more than 80 characters per line, including the newline character.\"}"));
+		assert!(output.contains("{\"id\":\"UnusedSuppression\","));
+		assert!(output.contains("\"results\":[{\"ruleId\":\"Goto\",\"ruleIndex\":1,\"level\":\"error\",\"message\":{\"text\":\"Goto used.\"},\
+			\"locations\":[{\"physicalLocation\":{\"artifactLocation\":{\"uri\":\"src/my%20file.c\"},\"region\":{\"startLine\":2,\"startColumn\":5,\"endColumn\":9}}}]}]"));
+	}
+}
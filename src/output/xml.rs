@@ -0,0 +1,121 @@
+use diagnostic::Diagnostic;
+use output::{Report, Summary};
+
+const HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+
+//A test case per checked file, failing with the list of its diagnostics.
+pub fn render_junit(reports: &[Report], summary: &Summary) -> String {
+	let mut output = String::from(HEADER);
+	output.push_str(&format!("<testsuites name=\"norme\" tests=\"{}\" failures=\"{}\">\n", summary.files, summary.files_with_diagnostics));
+	output.push_str(&format!("<testsuite name=\"norme\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n", summary.files, summary.files_with_diagnostics));
+
+	for report in reports.iter() {
+		let name = escape(&report.filename);
+		if report.diagnostics.is_empty() {
+			output.push_str(&format!("<testcase name=\"{}\" classname=\"norme\"/>\n", name));
+			continue;
+		}
+
+		let violations: String = report.diagnostics.iter().map(|d| format!("{}\n", escape(&location(d)))).collect();
+		output.push_str(&format!("<testcase name=\"{}\" classname=\"norme\">\n", name));
+		output.push_str(&format!("<failure message=\"{} violation(s)\" type=\"norme\">{}</failure>\n", report.diagnostics.len(), violations));
+		output.push_str("</testcase>\n");
+	}
+	output.push_str("</testsuite>\n</testsuites>\n");
+
+	return output;
+}
+
+//An <error> per diagnostic, grouped in the <file> they were found in. Columns start at 1.
+pub fn render_checkstyle(reports: &[Report]) -> String {
+	let mut output = String::from(HEADER);
+	output.push_str("<checkstyle version=\"4.3\">\n");
+
+	for report in reports.iter() {
+		output.push_str(&format!("<file name=\"{}\">\n", escape(&report.filename)));
+		for diagnostic in report.diagnostics.iter() {
+			let mut attributes = String::new();
+			if let Some(line) = diagnostic.line {
+				attributes.push_str(&format!(" line=\"{}\"", line));
+			}
+			if let Some((start, _)) = diagnostic.columns {
+				attributes.push_str(&format!(" column=\"{}\"", start + 1));
+			}
+			output.push_str(&format!("<error{} severity=\"{}\" message=\"{}\" source=\"norme.{}\"/>\n",
+				attributes, diagnostic.severity.name(), escape(&diagnostic.message), diagnostic.rule));
+		}
+		output.push_str("</file>\n");
+	}
+	output.push_str("</checkstyle>\n");
+
+	return output;
+}
+
+fn location(diagnostic: &Diagnostic) -> String {
+	match diagnostic.line {
+		Some(line) => format!("{}:{}: [{}] {}", diagnostic.filename, line, diagnostic.rule, diagnostic.message),
+		None => format!("{}: [{}] {}", diagnostic.filename, diagnostic.rule, diagnostic.message)
+	}
+}
+
+//Text usable in attributes as well as in elements. Control characters are not allowed in XML 1.0, they are dropped.
+pub fn escape(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			'\n' => escaped.push_str("&#10;"),
+			'\t' => escaped.push_str("&#9;"),
+			c if (c as u32) < 0x20 => {},
+			c => escaped.push(c)
+		}
+	}
+
+	return escaped;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn reports() -> Vec<Report> {
+		vec![
+			Report { filename: String::from("a.c"), diagnostics: vec![Diagnostic::new("Goto", "a.c", 2, "Goto used.").columns(4, 8)], content: String::new(), diff: Vec::new() },
+			Report { filename: String::from("b&c.h"), diagnostics: vec![Diagnostic::file("HeaderGuard", "b&c.h", "Missing <guard>.")], content: String::new(), diff: Vec::new() },
+			Report { filename: String::from("d.c"), diagnostics: Vec::new(), content: String::new(), diff: Vec::new() }
+		]
+	}
+
+	#[test]
+	fn junit() {
+		let reports = reports();
+		assert_eq!(render_junit(&reports, &Summary::new(&reports)), "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+			<testsuites name=\"norme\" tests=\"3\" failures=\"2\">\n\
+			<testsuite name=\"norme\" tests=\"3\" failures=\"2\" errors=\"0\">\n\
+			<testcase name=\"a.c\" classname=\"norme\">\n\
+			<failure message=\"1 violation(s)\" type=\"norme\">a.c:2: [Goto] Goto used.\n</failure>\n</testcase>\n\
+			<testcase name=\"b&amp;c.h\" classname=\"norme\">\n\
+			<failure message=\"1 violation(s)\" type=\"norme\">b&amp;c.h: [HeaderGuard] Missing &lt;guard&gt;.\n</failure>\n</testcase>\n\
+			<testcase name=\"d.c\" classname=\"norme\"/>\n\
+			</testsuite>\n</testsuites>\n");
+	}
+
+	#[test]
+	fn checkstyle() {
+		assert_eq!(render_checkstyle(&reports()), "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n\
+			<file name=\"a.c\">\n<error line=\"2\" column=\"5\" severity=\"error\" message=\"Goto used.\" source=\"norme.Goto\"/>\n</file>\n\
+			<file name=\"b&amp;c.h\">\n<error severity=\"error\" message=\"Missing &lt;guard&gt;.\" source=\"norme.HeaderGuard\"/>\n</file>\n\
+			<file name=\"d.c\">\n</file>\n</checkstyle>\n");
+	}
+
+	#[test]
+	fn escapes() {
+		assert_eq!(escape("a<b>&\"'\u{1}\n"), "a&lt;b&gt;&amp;&quot;&apos;&#10;");
+	}
+}
@@ -0,0 +1,425 @@
+use lexer;
+use lexer::{Token, TokenKind};
+
+//Keywords that can start a declaration in a block.
+pub const DECLARATION_KEYWORDS: [&str; 23] = [
+	"auto", "char", "const", "double", "enum", "extern", "float", "int", "long", "register", "restrict", "short",
+	"signed", "static", "struct", "union", "unsigned", "void", "volatile", "_Atomic", "_Bool", "_Complex", "_Thread_local"
+];
+
+//Top level declarations of a file, found without resolving types nor expanding macros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Declaration<'a> {
+	Function(Function<'a>),
+	Aggregate(Aggregate<'a>),//Definition of a struct, union or enum that declares nothing else.
+	Typedef(Typedef<'a>),
+	Variable(Variable<'a>)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function<'a> {
+	pub name: Token<'a>,
+	pub return_type: Vec<Token<'a>>,//Every token before the name, storage class included.
+	pub parameters: Vec<Parameter<'a>>,//Empty for both '()' and '(void)'.
+	pub parenthesis: (Token<'a>, Token<'a>),
+	pub body: Option<(Token<'a>, Token<'a>)>,//Braces of the definition, None for a prototype.
+	pub locals: Vec<Variable<'a>>//Declarations of its blocks.
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter<'a> {
+	pub name: Option<Token<'a>>,
+	pub tokens: Vec<Token<'a>>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregate<'a> {
+	pub keyword: Token<'a>,//'struct', 'union' or 'enum'.
+	pub tag: Option<Token<'a>>,
+	pub braces: (Token<'a>, Token<'a>),
+	pub fields: Vec<Field<'a>>//Enumerators for an enum.
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field<'a> {
+	pub names: Vec<Token<'a>>,
+	pub tokens: Vec<Token<'a>>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Typedef<'a> {
+	pub alias: Option<Token<'a>>,
+	pub aggregate: Option<Aggregate<'a>>,
+	pub tokens: Vec<Token<'a>>//From 'typedef' to the alias included.
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable<'a> {
+	pub names: Vec<Token<'a>>,
+	pub aggregate: Option<Aggregate<'a>>,
+	pub tokens: Vec<Token<'a>>//Up to the ';' excluded.
+}
+
+impl<'a> Declaration<'a> {
+	//Struct, union or enum defined by the declaration, if any.
+	pub fn aggregate(&self) -> Option<&Aggregate<'a>> {
+		match *self {
+			Declaration::Function(_) => None,
+			Declaration::Aggregate(ref aggregate) => Some(aggregate),
+			Declaration::Typedef(ref typedef) => typedef.aggregate.as_ref(),
+			Declaration::Variable(ref variable) => variable.aggregate.as_ref()
+		}
+	}
+}
+
+
+pub fn parse<'a>(tokens: &[Token<'a>]) -> Vec<Declaration<'a>> {
+	let code: Vec<Token> = tokens.iter().filter(|t| t.is_code() && !t.preprocessor).cloned().collect();
+	let mut declarations = Vec::new();
+
+	let mut start = 0;
+	let mut index = 0;
+	while index < code.len() {
+		let token = &code[index];
+		if token.is_punctuator(";") || token.is_punctuator("}") {
+			//An unbalanced '}' is treated as a separator to recover.
+			declarations.extend(declaration(&code[start..index]));
+			start = index + 1;
+		}
+		else if token.is_punctuator("{") {
+			//A truncated file is left to the last declaration.
+			let close = match lexer::matching_close(&code, index) {
+				Some(close) => close,
+				None => break
+			};
+			if let Some(name) = function_name(&code[start..index]) {
+				if code[index - 1].is_punctuator(")") {
+					let locals = locals(&code[index + 1..close]);
+					declarations.extend(function(&code[start..index], name, Some((code[index], code[close])), locals));
+					start = close + 1;
+				}
+			}
+			index = close;
+		}
+		else if token.is_punctuator("(") || token.is_punctuator("[") {
+			index = lexer::matching_close(&code, index).unwrap_or(code.len());
+		}
+		index += 1;
+	}
+	declarations.extend(declaration(&code[start.min(code.len())..]));
+
+	return declarations;
+}
+
+//Everything between two top level ';'.
+fn declaration<'a>(tokens: &[Token<'a>]) -> Option<Declaration<'a>> {
+	if tokens.is_empty() {
+		return None;
+	}
+
+	let aggregate = aggregate(tokens);
+	if tokens[0].is_keyword("typedef") {
+		let alias = declarator_name(&tokens[1..]).map(|index| index + 1);
+		return Some(Declaration::Typedef(Typedef {
+			alias: alias.map(|index| tokens[index]),
+			aggregate,
+			tokens: tokens[..alias.map_or(tokens.len(), |index| index + 1)].to_vec()
+		}));
+	}
+
+	let declarators = split(tokens, ",");
+	if declarators.len() == 1 {
+		if let Some(name) = function_name(tokens) {
+			return function(tokens, name, None, Vec::new());
+		}
+	}
+
+	let names: Vec<Token> = declarators.iter().filter_map(|d| declarator_name(d).map(|index| d[index])).collect();
+	if !names.is_empty() {
+		return Some(Declaration::Variable(Variable { names, aggregate, tokens: tokens.to_vec() }));
+	}
+	return aggregate.map(Declaration::Aggregate);
+}
+
+fn function<'a>(tokens: &[Token<'a>], name: usize, body: Option<(Token<'a>, Token<'a>)>, locals: Vec<Variable<'a>>) -> Option<Declaration<'a>> {
+	let close = lexer::matching_close(tokens, name + 1)?;
+
+	let inside = &tokens[name + 2..close];
+	let parameters = if inside.is_empty() || (inside.len() == 1 && inside[0].is_keyword("void")) {
+		Vec::new()
+	}
+	else {
+		split(inside, ",").into_iter().map(|parameter| Parameter {
+			name: parameter_name(parameter).map(|index| parameter[index]),
+			tokens: parameter.to_vec()
+		}).collect()
+	};
+
+	return Some(Declaration::Function(Function {
+		name: tokens[name],
+		return_type: tokens[..name].to_vec(),
+		parameters,
+		parenthesis: (tokens[name + 1], tokens[close]),
+		body,
+		locals
+	}));
+}
+
+//Declarations of variables between the braces of a function, in nested blocks too.
+fn locals<'a>(body: &[Token<'a>]) -> Vec<Variable<'a>> {
+	let mut locals = Vec::new();
+
+	let mut start = 0;
+	let mut index = 0;
+	while index < body.len() {
+		let token = &body[index];
+		if token.is_punctuator(";") {
+			let statement = &body[start..index];
+			if is_local_declaration(statement) {
+				let names = split(statement, ",").iter().filter_map(|d| declarator_name(d).map(|index| d[index])).collect();
+				locals.push(Variable { names, aggregate: aggregate(statement), tokens: statement.to_vec() });
+			}
+			start = index + 1;
+		}
+		else if token.is_punctuator("{") {
+			//Initializers and struct definitions belong to the statement, other braces open a block.
+			let statement = &body[start..index];
+			if statement.iter().any(|t| t.is_punctuator("=")) || is_local_declaration(statement) {
+				index = lexer::matching_close(body, index).unwrap_or(body.len());
+			}
+			else {
+				start = index + 1;
+			}
+		}
+		else if token.is_punctuator("}") {
+			start = index + 1;
+		}
+		else if token.is_punctuator("(") || token.is_punctuator("[") {
+			index = lexer::matching_close(body, index).unwrap_or(body.len());
+		}
+		index += 1;
+	}
+
+	return locals;
+}
+
+//Starts with a type: a keyword, or a name followed by a declarator as in "t_list *node" or "size_t i = 0".
+fn is_local_declaration(tokens: &[Token]) -> bool {
+	match tokens.first() {
+		Some(first) if first.kind == TokenKind::Keyword => DECLARATION_KEYWORDS.contains(&first.text),
+		Some(first) if first.kind == TokenKind::Identifier => {
+			let rest: Vec<&Token> = tokens[1..].iter().skip_while(|t| t.is_punctuator("*")).collect();
+			rest.first().is_some_and(|t| t.kind == TokenKind::Identifier)
+				&& rest.get(1).is_none_or(|t| ["=", ",", "["].iter().any(|p| t.is_punctuator(p)))
+		},
+		_ => false
+	}
+}
+
+//Index of the name when the declaration declares a function, e.g. "f" in "int *f(void)".
+fn function_name(tokens: &[Token]) -> Option<usize> {
+	declarator_name(tokens).filter(|index| tokens.get(index + 1).is_some_and(|t| t.is_punctuator("(")))
+}
+
+//First struct, union or enum that is defined with braces, None when they aren't closed.
+fn aggregate<'a>(tokens: &[Token<'a>]) -> Option<Aggregate<'a>> {
+	let start = tokens.iter().position(|t| ["struct", "union", "enum"].iter().any(|k| t.is_keyword(k)))?;
+	let tag = tokens.get(start + 1).filter(|t| t.kind == TokenKind::Identifier).cloned();
+	let open = start + 1 + tag.map_or(0, |_| 1);
+	if !tokens.get(open).is_some_and(|t| t.is_punctuator("{")) {
+		return None;
+	}
+	let close = lexer::matching_close(tokens, open)?;
+
+	let keyword = tokens[start];
+	let inside = &tokens[open + 1..close];
+	let fields = if keyword.is_keyword("enum") {
+		split(inside, ",").into_iter().filter(|f| !f.is_empty()).map(|enumerator| Field {
+			names: enumerator.iter().take(1).filter(|t| t.kind == TokenKind::Identifier).cloned().collect(),
+			tokens: enumerator.to_vec()
+		}).collect()
+	}
+	else {
+		split(inside, ";").into_iter().filter(|f| !f.is_empty()).map(|field| Field {
+			names: split(field, ",").iter().filter_map(|d| declarator_name(d).map(|index| d[index])).collect(),
+			tokens: field.to_vec()
+		}).collect()
+	};
+
+	return Some(Aggregate { keyword, tag, braces: (tokens[open], tokens[close]), fields });
+}
+
+//Index of the identifier being declared, skipping parameter lists, array sizes, bodies and initializers.
+fn declarator_name(tokens: &[Token]) -> Option<usize> {
+	let mut name = None;
+
+	let mut index = 0;
+	while index < tokens.len() {
+		let token = &tokens[index];
+		if token.is_punctuator("=") || token.is_punctuator(":") {
+			break;//Initializer or bit-field width.
+		}
+		else if token.is_punctuator("{") || token.is_punctuator("[") || is_parameter_list(tokens, index) {
+			index = lexer::matching_close(tokens, index).unwrap_or(tokens.len());
+		}
+		else if token.kind == TokenKind::Identifier {
+			let is_tag = index > 0 && ["struct", "union", "enum"].iter().any(|k| tokens[index - 1].is_keyword(k));
+			if !is_tag {
+				name = Some(index);
+			}
+		}
+		index += 1;
+	}
+
+	return name;
+}
+
+//A parenthesis following a name or another list, rather than one grouping a declarator as in "(*name)".
+fn is_parameter_list(tokens: &[Token], index: usize) -> bool {
+	tokens[index].is_punctuator("(") && index > 0 && (tokens[index - 1].kind == TokenKind::Identifier || tokens[index - 1].is_punctuator(")"))
+}
+
+//Like declarator_name, but a lone identifier is a type: "t_list *" has no name.
+fn parameter_name(tokens: &[Token]) -> Option<usize> {
+	const QUALIFIERS: [&str; 4] = ["const", "volatile", "restrict", "register"];
+
+	declarator_name(tokens).filter(|&name| tokens[..name].iter().any(|t| {
+		t.kind == TokenKind::Identifier || (t.kind == TokenKind::Keyword && !QUALIFIERS.contains(&t.text))
+	}))
+}
+
+//Splits on a punctuator that is not nested in any parenthesis, bracket or brace.
+pub fn split<'t, 'a>(tokens: &'t [Token<'a>], separator: &str) -> Vec<&'t [Token<'a>]> {
+	let mut parts = Vec::new();
+	let mut depth: usize = 0;
+	let mut start = 0;
+
+	for (index, token) in tokens.iter().enumerate() {
+		if ["(", "[", "{"].iter().any(|p| token.is_punctuator(p)) {
+			depth += 1;
+		}
+		else if [")", "]", "}"].iter().any(|p| token.is_punctuator(p)) {
+			depth = depth.saturating_sub(1);
+		}
+		else if depth == 0 && token.is_punctuator(separator) {
+			parts.push(&tokens[start..index]);
+			start = index + 1;
+		}
+	}
+	parts.push(&tokens[start..]);
+
+	return parts;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse_str(content: &str) -> Vec<Declaration<'_>> {
+		parse(&lexer::tokenize(content))
+	}
+
+	#[test]
+	fn functions() {
+		let declarations = parse_str("static int *f(int a, t_list *)\n{\n    return (0);\n}\nvoid g(void);\nh();");
+		assert_eq!(declarations.len(), 3);
+
+		match declarations[0] {
+			Declaration::Function(ref function) => {
+				assert_eq!(function.name.text, "f");
+				assert_eq!(function.return_type.iter().map(|t| t.text).collect::<Vec<&str>>(), ["static", "int", "*"]);
+				assert_eq!(function.parameters.len(), 2);
+				assert_eq!(function.parameters[0].name.map(|t| t.text), Some("a"));
+				assert_eq!(function.parameters[1].name, None);
+				assert_eq!(function.body.map(|(open, close)| (open.line, close.line)), Some((2, 4)));
+			},
+			_ => panic!("f is a function")
+		}
+		match declarations[1] {
+			Declaration::Function(ref function) => {
+				assert_eq!(function.name.text, "g");
+				assert!(function.parameters.is_empty());
+				assert!(function.body.is_none());
+			},
+			_ => panic!("g is a prototype")
+		}
+		match declarations[2] {
+			Declaration::Function(ref function) => assert_eq!(function.name.text, "h"),
+			_ => panic!("h is a prototype")
+		}
+	}
+
+	#[test]
+	fn function_pointers() {
+		let declarations = parse_str("typedef void (*f_handler)(int);\nint (*g_fn)(int);\nint (*signal(int sig))(int);");
+
+		match declarations[0] {
+			Declaration::Typedef(ref typedef) => assert_eq!(typedef.alias.map(|t| t.text), Some("f_handler")),
+			_ => panic!("f_handler is a typedef")
+		}
+		match declarations[1] {
+			Declaration::Variable(ref variable) => assert_eq!(variable.names[0].text, "g_fn"),
+			_ => panic!("g_fn is a variable")
+		}
+		match declarations[2] {
+			Declaration::Function(ref function) => assert_eq!(function.name.text, "signal"),
+			_ => panic!("signal is a function")
+		}
+	}
+
+	#[test]
+	fn aggregates() {
+		let declarations = parse_str("typedef struct s_a\n{\n    int x, y;\n    char *name;\n} t_a;\nenum e_b { A, B = 2 };\nstruct s_c g_c = { 0 };\nstruct s_d;");
+		assert_eq!(declarations.len(), 3);
+
+		match declarations[0] {
+			Declaration::Typedef(ref typedef) => {
+				assert_eq!(typedef.alias.map(|t| t.text), Some("t_a"));
+				let aggregate = typedef.aggregate.as_ref().unwrap();
+				assert_eq!(aggregate.tag.map(|t| t.text), Some("s_a"));
+				assert_eq!(aggregate.fields.len(), 2);
+				assert_eq!(aggregate.fields[0].names.iter().map(|t| t.text).collect::<Vec<&str>>(), ["x", "y"]);
+				assert_eq!(aggregate.fields[1].names[0].text, "name");
+			},
+			_ => panic!("t_a is a typedef")
+		}
+		match declarations[1] {
+			Declaration::Aggregate(ref aggregate) => {
+				assert!(aggregate.keyword.is_keyword("enum"));
+				assert_eq!(aggregate.fields.iter().map(|f| f.names[0].text).collect::<Vec<&str>>(), ["A", "B"]);
+			},
+			_ => panic!("e_b is an aggregate")
+		}
+		match declarations[2] {
+			Declaration::Variable(ref variable) => {
+				assert_eq!(variable.names[0].text, "g_c");
+				assert!(variable.aggregate.is_none());
+			},
+			_ => panic!("g_c is a variable")
+		}
+	}
+
+	#[test]
+	fn locals() {
+		let declarations = parse_str("int f(void)\n{\n    t_list *node = g(a, b);\n    int i, j[2] = {1, 2};\n    struct s_a a = {0};\n\n    i = 0;\n    a * b;\n    if (i)\n    {\n        size_t n;\n    }\n    return (x);\n}");
+
+		match declarations[0] {
+			Declaration::Function(ref function) => {
+				let names: Vec<Vec<&str>> = function.locals.iter().map(|l| l.names.iter().map(|t| t.text).collect()).collect();
+				assert_eq!(names, [vec!["node"], vec!["i", "j"], vec!["a"], vec!["b"], vec!["n"]]);
+				assert_eq!(function.locals[0].tokens.len(), 10);
+			},
+			_ => panic!("f is a function")
+		}
+	}
+
+	#[test]
+	fn truncated() {
+		//Unclosed braces don't define anything.
+		assert_eq!(parse_str("enum {").len(), 0);
+		assert_eq!(parse_str("struct s {").len(), 0);
+		assert_eq!(parse_str("int f() {").len(), 1);
+		assert_eq!(parse_str("int f(void)\n{\n    int a = {").len(), 1);
+	}
+}
@@ -1,4 +1,8 @@
 use rules::Rule;
+use diagnostic::Diagnostic;
+use source::SourceFile;
+use lexer;
+use lexer::{Token, TokenKind};
 
 
 
@@ -13,18 +17,34 @@ impl ControlStructuresIndentation {
 }
 
 impl Rule for ControlStructuresIndentation {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"ControlStructuresIndentation"
+	}
+
+	fn description(&self) -> String {
+		String::from("Control structure keywords, return and sizeof must be followed by a whitespace before their parenthesis.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+		let tokens = &file.tokens;
 
-		for line in content.lines() {
-			for element in ["else if", "if", "for", "while", "switch", "return", "sizeof"].iter() {
-				if line.trim_left().starts_with(element) && line.contains("(") && !line.contains(&(String::from(*element) + " (")) {
-					errors.push(format!("[{}:{}]{} must be followed by ' ('.", filename, line_number, element));
-				}
+		for (index, token) in tokens.iter().enumerate() {
+			if !["if", "for", "while", "switch", "return", "sizeof"].iter().any(|x| token.is_keyword(x)) {
+				continue;
 			}
 
-			line_number += 1;
+			match tokens.get(index + 1) {
+				Some(next) if next.is_punctuator("(") && &file.content[token.end()..next.offset] != " " => {
+					let mut diagnostic = Diagnostic::new(self.id(), file.filename, token.line, format!("{} must be followed by ' ('.", token.text))
+						.columns(token.column, token.column + token.text.len());
+					if file.content[token.end()..next.offset].trim().is_empty() {
+						diagnostic = diagnostic.edit(token.end(), next.offset, " ");
+					}
+					errors.push(diagnostic);
+				},
+				_ => {}
+			}
 		}
 
 		return errors;
@@ -44,18 +64,46 @@ impl SpecialControlStructuresIndentation {
 }
 
 impl Rule for SpecialControlStructuresIndentation {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"SpecialControlStructuresIndentation"
+	}
+
+	fn description(&self) -> String {
+		String::from("return without argument, continue and break must be directly followed by a semicolon, and return arguments must be in parentheses.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+		let tokens = &file.tokens;
 
-		for line in content.lines() {
-			for element in ["return", "break", "continue"].iter() {
-				if line.trim_left().starts_with(element) && !line.contains("(") && !line.contains(&(String::from(*element) + ";")) {
-					errors.push(format!("[{}:{}]{} must be directly followed by ';'.", filename, line_number, element));
-				}
+		for (index, token) in tokens.iter().enumerate() {
+			if !["return", "break", "continue"].iter().any(|x| token.is_keyword(x)) {
+				continue;
 			}
 
-			line_number += 1;
+			match tokens.get(index + 1) {
+				Some(next) if next.is_punctuator(";") && next.offset == token.end() => {},
+				Some(next) if next.is_punctuator("(") && token.is_keyword("return") => {},
+				next => {
+					let mut diagnostic = Diagnostic::new(self.id(), file.filename, token.line, format!("{} must be directly followed by ';'.", token.text))
+						.columns(token.column, token.column + token.text.len());
+
+					let end = tokens[index + 1..].iter()
+						.take_while(|t| t.preprocessor == token.preprocessor && !t.is_punctuator("{") && !t.is_punctuator("}"))
+						.find(|t| t.is_punctuator(";"));
+					match (next, end) {
+						(Some(next), _) if next.is_punctuator(";") && file.content[token.end()..next.offset].trim().is_empty() => {
+							diagnostic = diagnostic.edit(token.end(), next.offset, "");
+						},
+						//"return value;" becomes "return (value);".
+						(Some(next), Some(end)) if token.is_keyword("return") && file.content[token.end()..next.offset].trim().is_empty() => {
+							diagnostic = diagnostic.edit(token.end(), next.offset, " (").edit(end.offset, end.offset, ")");
+						},
+						_ => {}
+					}
+					errors.push(diagnostic);
+				}
+			}
 		}
 
 		return errors;
@@ -75,21 +123,33 @@ impl SwitchEnum {
 }
 
 impl Rule for SwitchEnum {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"SwitchEnum"
+	}
+
+	fn description(&self) -> String {
+		String::from("Switch must only be used with enumerations.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
-
-		for line in content.lines() {
-			for element in ["case", "default"].iter() {
-				if line.trim_left().starts_with(element) {
-					let without_element = line.replace(element, "");
-					if without_element.to_uppercase() != without_element || without_element.find(char::is_numeric).is_some() {
-						errors.push(format!("[{}:{}]Switch must only be used on enums.", filename, line_number));
-					}
-				}
+		let tokens = &file.tokens;
+
+		for (index, token) in tokens.iter().enumerate() {
+			if !token.is_keyword("case") {
+				continue;
 			}
 
-			line_number += 1;
+			let is_enum = tokens[index + 1..].iter()
+				.take_while(|t| t.line == token.line && !t.is_punctuator(":"))
+				.all(|t| match t.kind {
+					TokenKind::Number | TokenKind::Character | TokenKind::String => false,
+					TokenKind::Identifier => t.text.to_uppercase() == t.text,
+					_ => true
+				});
+			if !is_enum {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Switch must only be used on enums.").columns(token.column, token.column + token.text.len()));
+			}
 		}
 
 		return errors;
@@ -98,6 +158,38 @@ impl Rule for SwitchEnum {
 
 
 
+//Opening and closing brace indexes of every switch body, in the given code tokens.
+fn switch_bodies(code: &[Token]) -> Vec<(usize, usize)> {
+	let mut bodies = Vec::new();
+
+	for (index, token) in code.iter().enumerate() {
+		if !token.is_keyword("switch") || !code.get(index + 1).is_some_and(|t| t.is_punctuator("(")) {
+			continue;
+		}
+
+		if let Some(condition_end) = lexer::matching_close(code, index + 1) {
+			let open = condition_end + 1;
+			if code.get(open).is_some_and(|t| t.is_punctuator("{")) {
+				if let Some(close) = lexer::matching_close(code, open) {
+					bodies.push((open, close));
+				}
+			}
+		}
+	}
+
+	return bodies;
+}
+
+//Indexes of the tokens belonging to the given switch body, nested switches excluded.
+fn switch_tokens(bodies: &[(usize, usize)], body: (usize, usize)) -> Vec<usize> {
+	let (open, close) = body;
+
+	(open + 1..close)
+		.filter(|i| !bodies.iter().any(|&(o, c)| o > open && c < close && o <= *i && *i <= c))
+		.collect()
+}
+
+
 
 pub struct SwitchDefaultCase {
 	
@@ -109,41 +201,27 @@ impl SwitchDefaultCase {
 	}
 }
 
-//Expect ControlStructuresIndentation rule to be true.
 impl Rule for SwitchDefaultCase {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"SwitchDefaultCase"
+	}
 
-		let mut in_switch = false;
-		let mut switch_indentation = 0;
-		let mut found_default_case = false;
+	fn description(&self) -> String {
+		String::from("Switch must contain a 'default' case.")
+	}
 
-		for line in content.lines() {
-			if line.trim_left().starts_with("switch (") {
-				in_switch = true;
-				found_default_case = false;
-				switch_indentation = 0;
-			}
-			if in_switch {
-				if line.contains("{") {
-					switch_indentation += 1;
-				}
-				if switch_indentation > 0 && line.contains("}") {
-					switch_indentation -= 1;
-					if switch_indentation == 0 {
-						in_switch = false;
-						if !found_default_case {
-							errors.push(format!("[{}:{}]Missing default case for the switch statement.", filename, line_number));
-						}
-					}
-				}
-				if line.trim_left().starts_with("default") {
-					found_default_case = true;
-				}
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+		let tokens = &file.tokens;
+		let code: Vec<Token> = tokens.iter().filter(|t| t.is_code()).cloned().collect();
+		let bodies = switch_bodies(&code);
+
+		for body in bodies.iter() {
+			if !switch_tokens(&bodies, *body).iter().any(|i| code[*i].is_keyword("default")) {
+				let close = &code[body.1];
+				errors.push(Diagnostic::new(self.id(), file.filename, close.line, "Missing default case for the switch statement.")
+					.columns(close.column, close.column + 1));
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -163,45 +241,41 @@ impl SwitchEnd {
 }
 
 impl Rule for SwitchEnd {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"SwitchEnd"
+	}
+
+	fn description(&self) -> String {
+		String::from("All switch cases must end with 'break' or 'return'.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+		let tokens = &file.tokens;
+		let code: Vec<Token> = tokens.iter().filter(|t| t.is_code()).cloned().collect();
+		let bodies = switch_bodies(&code);
 
-		let mut in_switch = false;
-		let mut switch_indentation = 0;
-		let mut have_seen_case_end = false;
+		for body in bodies.iter() {
+			let mut have_seen_case_end = true;
 
-		for line in content.lines() {
-			if line.trim_left().starts_with("switch (") {
-				in_switch = true;
-				have_seen_case_end = true;
-				switch_indentation = 0;
-			}
-			if in_switch {
-				if line.contains("{") {
-					switch_indentation += 1;
-				}
-				if switch_indentation > 0 && line.contains("}") {
-					switch_indentation -= 1;
-					if switch_indentation == 0 {
-						in_switch = false;
-						if !have_seen_case_end {
-							errors.push(format!("[{}:{}]Missing return or break statement for the previous case.", filename, line_number));
-						}
-					}
-				}
-				if ["break", "return"].iter().any(|x| line.trim_left().starts_with(x)) {
+			for index in switch_tokens(&bodies, *body) {
+				let token = &code[index];
+				let first_on_line = code[index - 1].line != token.line;
+
+				if first_on_line && (token.is_keyword("break") || token.is_keyword("return")) {
 					have_seen_case_end = true;
 				}
-				if ["case", "default"].iter().any(|x| line.trim_left().starts_with(x)) {
+				if token.is_keyword("case") || token.is_keyword("default") {
 					if !have_seen_case_end {
-						errors.push(format!("[{}:{}]Missing return or break statement for the previous case.", filename, line_number));
+						errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Missing return or break statement for the previous case."));
 					}
 					have_seen_case_end = false;
 				}
 			}
 
-			line_number += 1;
+			if !have_seen_case_end {
+				errors.push(Diagnostic::new(self.id(), file.filename, code[body.1].line, "Missing return or break statement for the previous case."));
+			}
 		}
 
 		return errors;
@@ -242,7 +316,11 @@ mod tests {
 		assert_eq!(control_structures_indentation.verify("", "return   (i);").len(), 1);
 
 		assert_eq!(control_structures_indentation.verify("", "int name_while = f(arg);").len(), 0);
+		assert_eq!(control_structures_indentation.verify("", "iffy(condition);").len(), 0);
+		assert_eq!(control_structures_indentation.verify("", "x = sizeof(int);").len(), 1);
+
 
+		assert_eq!(control_structures_indentation.fix("", "if(a)\nwhile  (b)\nfor /* c */(;;)"), "if (a)\nwhile (b)\nfor /* c */(;;)");
 	}
 
 	#[test]
@@ -260,6 +338,8 @@ mod tests {
 		assert_eq!(special_control_structures_indentation.verify("", "continue\n;").len(), 1);
 		assert_eq!(special_control_structures_indentation.verify("", "break     ;").len(), 1);
 		assert_eq!(special_control_structures_indentation.verify("", "return").len(), 1);
+
+		assert_eq!(special_control_structures_indentation.fix("", "break ;\nreturn ;\nreturn a + b;"), "break;\nreturn;\nreturn (a + b);");
 	}
 
 	#[test]
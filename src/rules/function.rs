@@ -1,4 +1,15 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
 use rules::Rule;
+use diagnostic::Diagnostic;
+use source::{FileKind, SourceFile};
+use lexer;
+use lexer::{Token, TokenKind};
+use parser;
+use parser::Declaration;
 
 pub struct FunctionMaxCodeLines {
 	max_lines: usize
@@ -11,49 +22,40 @@ impl FunctionMaxCodeLines {
 }
 
 impl Rule for FunctionMaxCodeLines {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"FunctionMaxCodeLines"
+	}
+
+	fn description(&self) -> String {
+		format!("Function's body must not contain more than {} lines, excluding comments and blank lines.", self.max_lines)
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
-		let mut indentation = 0;
+		let mut indentation: usize = 0;
 		let mut nb_code_lines = 0;
-		let mut in_multi_line_comment = false;
 
-		for line in content.lines() {
-			if line.contains("}") {
-				indentation -= 1;
+		for line in file.lines().iter() {
+			let closing = line.count_punctuator("}");
+			if closing > 0 && indentation > 0 {
+				indentation = indentation.saturating_sub(closing);
 				if indentation == 0 && nb_code_lines > self.max_lines {
-					errors.push(format!("[{}:{}]Function body's line count excedeed. Expected at most {} got {}.", filename, line_number, self.max_lines, nb_code_lines));
+					errors.push(Diagnostic::new(self.id(), file.filename, line.number, format!("Function body's line count excedeed. Expected at most {} got {}.", self.max_lines, nb_code_lines)));
 				}
 			}
 
-			if indentation >= 1 {
-				let mut dont_have_code = line.trim().is_empty() || line.trim_left().starts_with("//") || in_multi_line_comment; 
-				if line.contains("/*") {
-					in_multi_line_comment = true;
-					dont_have_code = line.trim_left().starts_with("/*");
-				}
-
-				if line.contains("*/") && dont_have_code {
-					in_multi_line_comment = false;
-					dont_have_code = line.trim_right().ends_with("*/");
-				}
-
-				if !dont_have_code {
-					nb_code_lines += 1;
-				}
-
-				
+			if indentation >= 1 && line.has_code() {
+				nb_code_lines += 1;
 			}
 
-			if line.contains("{") {
-				indentation += 1;
-				if indentation <= 1 {
+			let opening = line.count_punctuator("{");
+			if opening > 0 {
+				if indentation == 0 {
 					nb_code_lines = 0;
 				}
+				indentation += opening;
 			}
-
-			line_number += 1;
 		}
 
 
@@ -74,45 +76,23 @@ impl FunctionMaxArguments {
 }
 
 impl Rule for FunctionMaxArguments {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
-
-		let mut indentation = 0;
-		let mut in_arguments = false;
-		let mut nb_arguments = 0;
-
-		for line in content.lines() {
-			if line.contains("{") {
-				indentation += 1;
-			}
-			if line.contains("}") && indentation > 0 {
-				indentation -= 1;
-			}
+	fn id(&self) -> &'static str {
+		"FunctionMaxArguments"
+	}
 
-			let mut line = line;
-			if indentation == 0 && line.contains("(") {
-				line = line.split("(").nth(1).unwrap();
-				in_arguments = true;
-				nb_arguments = 0;
-			}
+	fn description(&self) -> String {
+		format!("Function must not have more than {} arguments.", self.max_nb_arguments)
+	}
 
-			if in_arguments {
-				if line.contains(")") {
-					in_arguments = false;
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
 
-					line = line.split(")").next().unwrap();
-					nb_arguments += line.chars().filter(|c| *c == ',').count() + 1;// n ',' lead to n+1 arguments
-					if nb_arguments > self.max_nb_arguments {
-						errors.push(format!("[{}:{}]Too many function arguments. Expected at most {} got {}.", filename, line_number, self.max_nb_arguments, nb_arguments));
-					}
-				}
-				else {
-					nb_arguments += line.chars().filter(|c| *c == ',').count();
-				}
+		for function in file.functions() {
+			let nb_arguments = function.parameters.len();
+			if nb_arguments > self.max_nb_arguments {
+				errors.push(Diagnostic::new(self.id(), file.filename, function.name.line, format!("Too many function arguments. Expected at most {} got {}.", self.max_nb_arguments, nb_arguments))
+					.columns(function.name.column, function.name.column + function.name.text.len()));
 			}
-
-			line_number += 1;
 		}
 
 
@@ -132,47 +112,36 @@ impl FunctionBlankLines {
 }
 
 impl Rule for FunctionBlankLines {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"FunctionBlankLines"
+	}
+
+	fn description(&self) -> String {
+		String::from("Function's subparts must be separated by one blank line at most.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
-		let mut indentation = 0;
+		let mut indentation: usize = 0;
 		let mut is_previous_blank = false;
-		let mut in_multi_line_comment = false;
 
-		for line in content.lines() {
-			if line.contains("{") {
-				indentation += 1;
-			}
-			if line.contains("}") && indentation > 0 {
-				indentation -= 1;
-			}
+		for line in file.lines().iter() {
+			indentation += line.count_punctuator("{");
+			indentation = indentation.saturating_sub(line.count_punctuator("}"));
 
 			if indentation > 0 {
-				if line.trim().is_empty() && !in_multi_line_comment {
+				if line.is_blank() {
 					if is_previous_blank {
-						errors.push(format!("[{}:{}]Two following blank line found.", filename, line_number));
+						errors.push(Diagnostic::new(self.id(), file.filename, line.number, "Two following blank line found."));
 					}
 					is_previous_blank = true;
 				}
 				else
 				{
-					let mut dont_have_code = line.trim_left().starts_with("//") || in_multi_line_comment ; 
-					if line.contains("/*") {
-						in_multi_line_comment = true;
-						dont_have_code = line.trim_left().starts_with("/*");
-					}
-
-					if line.contains("*/") && dont_have_code {
-						in_multi_line_comment = false;
-						dont_have_code = line.trim_right().ends_with("*/");
-					}
-
-					is_previous_blank = dont_have_code && is_previous_blank;
+					is_previous_blank = !line.has_code() && is_previous_blank;
 				}
 			}
-
-			line_number += 1;
 		}
 
 
@@ -193,45 +162,35 @@ impl FunctionStartParenthesis {
 }
 
 impl Rule for FunctionStartParenthesis {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"FunctionStartParenthesis"
+	}
+
+	fn description(&self) -> String {
+		String::from("No whitespace between a function name and its opening parenthesis.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
-		let mut indentation = 0;
+		let mut indentation: usize = 0;
 
-		let mut in_macro = false;
-		
-		for line in content.lines() {
-			if line.contains("{") {
+		let tokens = &file.tokens;
+		let code: Vec<Token> = tokens.iter().filter(|t| t.is_code() && !t.preprocessor).cloned().collect();
+		for (index, token) in code.iter().enumerate() {
+			if token.is_punctuator("{") {
 				indentation += 1;
 			}
-			if line.contains("}") && indentation > 0 {
-				indentation -= 1;
-			}
-			
-
-			if line.trim_left().starts_with("#") {
-				in_macro = true;
+			else if token.is_punctuator("}") {
+				indentation = indentation.saturating_sub(1);
 			}
-
-			if !in_macro {
-				if indentation == 0 && !line.trim_left().starts_with("//") && !line.trim_left().starts_with("**") {
-					match line.chars().position(|x| x == '(') {
-						Some(p) if p > 0 => {
-							if line.chars().nth(p - 1).unwrap().is_whitespace() {
-								errors.push(format!("[{}:{}]Function parenthesis must be next to function name.", filename, line_number));
-							}
-						},
-						_ => (),
-					}
+			else if indentation == 0 && token.is_punctuator("(") && index > 0 {
+				let previous = &code[index - 1];
+				if previous.kind == TokenKind::Identifier && previous.end() != token.offset {
+					errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Function parenthesis must be next to function name.")
+						.columns(previous.column + previous.text.len(), token.column + 1));
 				}
 			}
-			else
-			{
-				in_macro = line.trim_right().ends_with("\\");
-			}
-
-			line_number += 1;
 		}
 
 
@@ -253,22 +212,37 @@ impl MaxFunctionsPerSourceFile {
 
 //This rule expect OwnLineBrace and IndentationLevel rule to be true
 impl Rule for MaxFunctionsPerSourceFile {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		if !filename.contains(".c") {
+	fn id(&self) -> &'static str {
+		"MaxFunctionsPerSourceFile"
+	}
+
+	fn description(&self) -> String {
+		format!("There must be at most {} functions per source file.", self.max_functions)
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		if file.kind != FileKind::Source {
 			return Vec::new();
 		}
 
 		let mut errors = Vec::new();
 		let mut nb_functions: usize = 0;
-		
-		for line in content.lines() {			
-			if line.starts_with("{"){
-				nb_functions += 1;
+		let mut indentation: usize = 0;
+
+		for token in file.tokens.iter().filter(|t| !t.preprocessor) {
+			if token.is_punctuator("{") {
+				if indentation == 0 && token.column == 0 {
+					nb_functions += 1;
+				}
+				indentation += 1;
+			}
+			else if token.is_punctuator("}") {
+				indentation = indentation.saturating_sub(1);
 			}
 		}
 
 		if nb_functions > self.max_functions {
-			errors.push(format!("[{}]Too much functions found. Expected at most {} functions, got {}.", filename, self.max_functions, nb_functions));
+			errors.push(Diagnostic::file(self.id(), file.filename, format!("Too much functions found. Expected at most {} functions, got {}.", self.max_functions, nb_functions)));
 		}
 
 
@@ -290,23 +264,24 @@ impl MaxExportedFunctions {
 
 //This rule expect the opening parenthesis of macro to be on the first line of the macro
 impl Rule for MaxExportedFunctions {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		if !filename.contains(".h") {
+	fn id(&self) -> &'static str {
+		"MaxExportedFunctions"
+	}
+
+	fn description(&self) -> String {
+		format!("There must be at most {} exported functions per header file.", self.max_functions)
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		if file.kind != FileKind::Header {
 			return Vec::new();
 		}
 
 		let mut errors = Vec::new();
-		let mut nb_functions: usize = 0;
-		
-		for line in content.lines() {			
-			if line.contains("(") && !line.starts_with("#") && !line.ends_with("\\") &&
-				!line.trim_left().starts_with("//") & &!line.trim_left().starts_with("**") {
-				nb_functions += 1;
-			}
-		}
+		let nb_functions = file.functions().count();
 
 		if nb_functions > self.max_functions {
-			errors.push(format!("[{}]Too much exported functions found. Expected at most {} functions, got {}.", filename, self.max_functions, nb_functions));
+			errors.push(Diagnostic::file(self.id(), file.filename, format!("Too much exported functions found. Expected at most {} functions, got {}.", self.max_functions, nb_functions)));
 		}
 
 
@@ -327,40 +302,29 @@ impl FunctionParametersIndentation {
 
 //This rule expect OwnLineBrace rule to be true
 impl Rule for FunctionParametersIndentation {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"FunctionParametersIndentation"
+	}
+
+	fn description(&self) -> String {
+		String::from("If function arguments are split over multiple lines, they must be aligned.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
-		
-		let mut indentation = 0;
-		let mut in_function_parameters = false;
-		let mut parameters_indentation = 0;
+		let lines = file.lines();
 
-		for line in content.lines() {
-			if line.contains("{") {
-				indentation += 1;
-			}
-			if line.contains("}") && indentation > 0 {
-				indentation -= 1;
-			}
+		for function in file.functions() {
+			let (open, close) = function.parenthesis;
+			let parameters_indentation = open.column + 1;
 
-			if indentation <= 0 {
-				if line.contains("(") {
-					in_function_parameters = true;
-					parameters_indentation = line.split("(").next().unwrap().len() + 1;
+			for line in lines[open.line..close.line].iter().filter(|l| l.has_code() && !l.is_preprocessor()) {
+				let current_indentation = line.text.len() - line.text.trim_start().len();
+				if current_indentation != parameters_indentation {
+					errors.push(Diagnostic::new(self.id(), file.filename, line.number, format!("Wrong parameters indentation level. Expected {} whitespaces, got {}.", parameters_indentation, current_indentation))
+						.columns(0, current_indentation));
 				}
-				else if in_function_parameters {
-					let current_indentation = line.len() - line.trim_left().len();
-					if current_indentation != parameters_indentation {
-						errors.push(format!("[{}:{}]Wrong parameters indentation level. Expected {} whitespaces, got {}.", filename, line_number, parameters_indentation, current_indentation));
-					}
-				}
-			}
-
-			if in_function_parameters && line.contains(")") {
-				in_function_parameters = false;
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -381,49 +345,208 @@ impl FunctionsPrototypeLocation {
 // This rule expect OwnLineBrace rule to be true
 // and macro functions end parenthesis to be on the first line of the macro.
 impl Rule for FunctionsPrototypeLocation {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		if !filename.contains(".c") {
+	fn id(&self) -> &'static str {
+		"FunctionsPrototypeLocation"
+	}
+
+	fn description(&self) -> String {
+		String::from("Function prototypes must only be located in header files.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		if file.kind != FileKind::Source {
 			return Vec::new();
 		}
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
-		
-		let mut indentation = 0;
 
-		let mut in_macro = false;
+		for function in file.functions().filter(|f| f.body.is_none()) {
+			errors.push(Diagnostic::new(self.id(), file.filename, function.name.line, "Functions prototype must be located in header file.")
+				.columns(function.name.column, function.name.column + function.name.text.len()));
+		}
 
-		for line in content.lines() {
-			if line.contains("{") {
-				indentation += 1;
-			}
-			if line.contains("}") && indentation > 0 {
-				indentation -= 1;
-			}
-			if line.trim_left().starts_with("#") {
-				in_macro = true;
+		return errors;
+	}
+}
+
+
+
+
+pub struct StructurePassedByAddress {
+	include: Vec<PathBuf>,//Directories the local headers are also looked for in.
+	headers: Mutex<HashMap<PathBuf, Option<Arc<Header>>>>,//Local headers read, by canonical path, shared by the files checked.
+	unreadable: Mutex<BTreeSet<String>>//Why headers couldn't be read, reported once for the whole run.
+}
+
+impl StructurePassedByAddress {
+	pub fn new(include: Vec<PathBuf>) -> StructurePassedByAddress {
+		StructurePassedByAddress { include, headers: Mutex::new(HashMap::new()), unreadable: Mutex::new(BTreeSet::new()) }
+	}
+
+	//Header read and parsed once for the whole run, None when it can't be read.
+	fn header(&self, path: &Path) -> Option<Arc<Header>> {
+		let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+		if let Some(header) = self.headers.lock().unwrap().get(&path) {
+			return header.clone();
+		}
+
+		let header = match fs::read_to_string(&path) {
+			Ok(content) => {
+				let tokens = lexer::tokenize(&content);
+				Some(Arc::new(Header { includes: includes(&tokens), aliases: aliases(&parser::parse(&tokens)) }))
+			},
+			Err(error) => {
+				self.unreadable.lock().unwrap().insert(format!("{}: Can't read the included header: {}, its typedefs are ignored.", path.display(), error));
+				None
 			}
+		};
+		self.headers.lock().unwrap().insert(path, header.clone());
+		return header;
+	}
 
-			if indentation <= 0 && line.contains(")") && line.contains(";")
-				&& !line.contains("=") && !in_macro {
-				errors.push(format!("[{}:{}]Functions prototype must be located in header file.", filename, line_number));
+	//Aliases of the local headers included, "#include "point.h"" being looked for next to the file then in the include
+	//directories, and of their own includes.
+	fn included_aggregates(&self, filename: &Path, includes: &[String], aggregates: &mut Vec<String>, visited: &mut Vec<PathBuf>) {
+		let directory = filename.parent().unwrap_or(Path::new(""));
+		for name in includes.iter() {
+			let path = match Some(directory).into_iter().chain(self.include.iter().map(PathBuf::as_path)).map(|d| d.join(name)).find(|p| p.exists()) {
+				Some(path) => path,
+				None => {
+					self.unreadable.lock().unwrap().insert(format!("{}: Included header not found next to the file including it or in the include directories, its typedefs are ignored.", name));
+					continue;
+				}
+			};
+			if visited.contains(&path) {
+				continue;
 			}
+			visited.push(path.clone());
 
-			if in_macro {
-				in_macro = line.trim_right().ends_with("\\");
+			if let Some(header) = self.header(&path) {
+				self.included_aggregates(&path, &header.includes, aggregates, visited);
+				resolve(&header.aliases, aggregates);
 			}
+		}
+	}
+}
+
+impl Rule for StructurePassedByAddress {
+	fn id(&self) -> &'static str {
+		"StructurePassedByAddress"
+	}
+
+	fn description(&self) -> String {
+		String::from("Structures and unions must be passed to and returned from functions by address.")
+	}
 
-			line_number += 1;
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+
+		let mut aggregates = Vec::new();
+		let mut visited = vec![PathBuf::from(file.filename)];
+		self.included_aggregates(Path::new(file.filename), &includes(&file.tokens), &mut aggregates, &mut visited);
+		resolve(&aliases(&file.declarations), &mut aggregates);
+
+		for function in file.functions() {
+			let types = function.parameters.iter().map(|p| (&p.tokens[..], p.name)).chain(Some((&function.return_type[..], None)));
+			for (tokens, name) in types {
+				if is_by_value(tokens, name, &aggregates) {
+					let (first, last) = (&tokens[0], &tokens[tokens.len() - 1]);
+					let end = if last.line == first.line { last.column + last.text.len() } else { first.column + first.text.len() };
+					errors.push(Diagnostic::new(self.id(), file.filename, first.line, "Structures and unions must be passed by address.").columns(first.column, end));
+				}
+			}
 		}
+		errors.sort_by_key(|e| (e.line, e.columns));
 
 		return errors;
 	}
+
+	fn warnings(&self) -> Vec<String> {
+		self.unreadable.lock().unwrap().iter().cloned().collect()
+	}
 }
 
+//Local header, with what the rule needs of it.
+struct Header {
+	includes: Vec<String>,
+	aliases: Vec<Alias>
+}
 
+//Typedef, reduced to what tells whether it names a structure or union by value.
+struct Alias {
+	name: String,
+	aggregate: bool,//A structure or union written out, as in "typedef struct s_point t_point;".
+	aliased: Vec<String>//Names it refers to, as in "typedef t_point t_vector;". Empty when behind a pointer.
+}
 
-#[cfg(tests)]
+//A structure or union, named by its tag or a typedef, that isn't behind a pointer. Arrays are passed by address.
+fn is_by_value(tokens: &[Token], name: Option<Token>, aggregates: &[String]) -> bool {
+	if tokens.iter().any(|t| ["*", "(", "["].iter().any(|p| t.is_punctuator(p))) {
+		return false;
+	}
+	return tokens.iter().any(|t| t.is_keyword("struct") || t.is_keyword("union")
+		|| (t.kind == TokenKind::Identifier && name.is_none_or(|n| n.offset != t.offset) && aggregates.iter().any(|a| a == t.text)));
+}
+
+//Names of the local headers included.
+fn includes(tokens: &[Token]) -> Vec<String> {
+	let mut includes = Vec::new();
+	for (index, token) in tokens.iter().enumerate() {
+		match tokens.get(index + 1) {
+			Some(header) if token.directive() == "include" && header.kind == TokenKind::String => {
+				includes.push(String::from(header.text.trim_matches('"')));
+			},
+			_ => {}
+		}
+	}
+
+	return includes;
+}
+
+fn aliases(declarations: &[Declaration]) -> Vec<Alias> {
+	let mut aliases = Vec::new();
+	for declaration in declarations.iter() {
+		let (tokens, alias) = match *declaration {
+			Declaration::Typedef(ref typedef) if typedef.alias.is_some() => (&typedef.tokens, typedef.alias.unwrap()),
+			_ => continue
+		};
+
+		let mut aliased = Vec::new();
+		let mut index = 0;
+		while index < tokens.len() {
+			if tokens[index].is_punctuator("{") {
+				index = lexer::matching_close(tokens, index).unwrap_or(tokens.len());
+			}
+			else if tokens[index].offset != alias.offset {
+				aliased.push(tokens[index]);
+			}
+			index += 1;
+		}
+		let by_value = is_by_value(&aliased, None, &[]);
+		let pointer = aliased.iter().any(|t| ["*", "(", "["].iter().any(|p| t.is_punctuator(p)));
+		aliases.push(Alias {
+			name: String::from(alias.text),
+			aggregate: by_value,
+			aliased: aliased.iter().filter(|t| t.kind == TokenKind::Identifier && !pointer).map(|t| String::from(t.text)).collect()
+		});
+	}
+
+	return aliases;
+}
+
+//Adds the aliases of structures and unions, or of other such aliases, in the order they are declared.
+fn resolve(aliases: &[Alias], aggregates: &mut Vec<String>) {
+	for alias in aliases.iter() {
+		if alias.aggregate || alias.aliased.iter().any(|name| aggregates.contains(name)) {
+			aggregates.push(alias.name.clone());
+		}
+	}
+}
+
+#[cfg(test)]
 mod tests {
 	use super::*;
+	use std::env;
+	use std::process;
 	#[test]
 	fn function_max_code_lines() {
 		let function_max_code_lines = FunctionMaxCodeLines::new(1);
@@ -449,6 +572,7 @@ mod tests {
 		assert_eq!(function_max_arguments.verify("", "something()\n{\n}").len(), 0);
 		assert_eq!(function_max_arguments.verify("", "something(int test)\n{\n}").len(), 0);
 		assert_eq!(function_max_arguments.verify("", "something(struct test temp)\n{\n}").len(), 0);
+		assert_eq!(function_max_arguments.verify("", "int g_x = f(a, b);").len(), 0);
 		
 		assert_eq!(function_max_arguments.verify("", "something(struct test temp, int testv2)\n{\n}").len(), 1);
 		assert_eq!(function_max_arguments.verify("", "something(struct test temp, int testv2, int v4)\n{\n}").len(), 1);
@@ -507,7 +631,7 @@ mod tests {
 		assert_eq!(max_exported_functions.verify(".c", "something();\nsomething();").len(), 0);
 		assert_eq!(max_exported_functions.verify(".h", "f();").len(), 0);
 
-		assert_eq!(max_exported_functions.verify(".h", "f()\ng()\n").len(), 1);
+		assert_eq!(max_exported_functions.verify(".h", "f();\ng();\n").len(), 1);
 		assert_eq!(max_exported_functions.verify(".h", "#m()\ng()").len(), 0);
 	}
 
@@ -535,5 +659,44 @@ mod tests {
 
 		assert_eq!(functions_prototype_location.verify(".c", "something(hello);\nsomething(world);").len(), 2);
 		assert_eq!(functions_prototype_location.verify(".c", "something(hello)\n{\n}\nsomething(world){}").len(), 0);
+		assert_eq!(functions_prototype_location.verify(".c", "typedef void (*f_handler)(int);\nint (*g_fn)(int);").len(), 0);
+	}
+
+	#[test]
+	fn structure_passed_by_address() {
+		let structure_passed_by_address = StructurePassedByAddress::new(Vec::new());
+
+		assert_eq!(structure_passed_by_address.verify("", "typedef struct s_point\n{\n    int *x;\n} t_point;\nvoid f(t_point *p, struct s_a *a, t_point t[2]);\nt_point *g(enum e_a a);").len(), 0);
+		assert_eq!(structure_passed_by_address.verify("", "typedef struct s_a *t_ptr;\ntypedef int t_int;\nvoid f(t_ptr p, t_int i, void (*h)(struct s_a a));").len(), 0);
+
+		assert_eq!(structure_passed_by_address.verify("", "typedef struct s_point t_point;\nvoid f(t_point p);").len(), 1);
+		assert_eq!(structure_passed_by_address.verify("", "typedef union u_a t_a;\ntypedef t_a t_b;\nt_b f(const t_b b)\n{\n}").len(), 2);
+		assert_eq!(structure_passed_by_address.verify("", "struct s_list g(struct s_list list, union u_a a);").len(), 3);
+
+		let root = env::temp_dir().join(format!("norme-aggregates-{}", process::id()));
+		fs::create_dir_all(root.join("include")).unwrap();
+		fs::create_dir_all(root.join("src")).unwrap();
+		fs::write(root.join("include/point.h"), "#include \"point.h\"\ntypedef struct s_point t_point;").unwrap();
+		fs::write(root.join("shapes.h"), "#include \"include/point.h\"\ntypedef t_point t_vector;").unwrap();
+		let filename = root.join("a.c");
+		let filename = filename.to_str().unwrap();
+		assert_eq!(structure_passed_by_address.verify(filename, "#include \"shapes.h\"\nvoid f(t_point a, t_vector *b, t_vector c);").len(), 2);
+		assert!(structure_passed_by_address.warnings().is_empty());
+
+		//Looked for in the include directories too.
+		let other = root.join("src/b.c");
+		let other = other.to_str().unwrap();
+		assert_eq!(structure_passed_by_address.verify(other, "#include \"point.h\"\nvoid f(t_point a);").len(), 0);
+		assert_eq!(structure_passed_by_address.warnings().len(), 1);
+		let included = StructurePassedByAddress::new(vec![root.join("include")]);
+		assert_eq!(included.verify(other, "#include \"point.h\"\nvoid f(t_point a);").len(), 1);
+		assert!(included.warnings().is_empty());
+
+		//A header missing for several files is reported once, and not as a violation.
+		let missing = StructurePassedByAddress::new(Vec::new());
+		assert_eq!(missing.verify(filename, "#include \"shapes.h\"\n#include \"missing.h\"\nvoid f(t_point a);").len(), 1);
+		assert_eq!(missing.verify(other, "#include \"missing.h\"\nvoid f(int a);").len(), 0);
+		assert_eq!(missing.warnings(), ["missing.h: Included header not found next to the file including it or in the include directories, its typedefs are ignored."]);
+		fs::remove_dir_all(&root).unwrap();
 	}
 }
@@ -1,6 +1,13 @@
 use rules::Rule;
-
-use std::str::Lines;
+use diagnostic::Diagnostic;
+use source::SourceFile;
+use lexer;
+use lexer::{Token, TokenKind};
+use source::{Line, Mask};
+use parser;
+use parser::Declaration;
+
+use std::slice::Iter;
 use std::iter::Peekable;
 
 pub struct LineSize {
@@ -14,16 +21,20 @@ impl LineSize {
 }
 
 impl Rule for LineSize {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"LineSize"
+	}
+
+	fn description(&self) -> String {
+		format!("No more than {} characters per line, including the newline character.", self.max)
+	}
 
-		for line in content.lines() {
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+		for (line_number, line) in (1..).zip(file.content.lines()) {
 			if line.len() >= self.max {
-				errors.push(format!("[{}:{}]Line size exceeded.", filename, line_number));
+				errors.push(Diagnostic::new(self.id(), file.filename, line_number, "Line size exceeded.").columns(self.max.saturating_sub(1), line.len()));
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -32,6 +43,9 @@ impl Rule for LineSize {
 
 
 
+//Columns between two tab stops, when expanding tabs.
+const TAB_SIZE: usize = 8;
+
 pub struct SpaceIndentation {
 
 }
@@ -43,16 +57,34 @@ impl SpaceIndentation {
 }
 
 impl Rule for SpaceIndentation {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"SpaceIndentation"
+	}
 
-		for line in content.lines() {
-			if line.contains("\t") {
-				errors.push(format!("[{}:{}]Tab used instead of spaces.", filename, line_number));
-			}
+	fn description(&self) -> String {
+		String::from("Spaces must be used instead of tabs.")
+	}
 
-			line_number += 1;
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+		for line in file.lines().iter() {
+			if let Some(index) = line.text.find('\t') {
+				let mut diagnostic = Diagnostic::new(self.id(), file.filename, line.number, "Tab used instead of spaces.").columns(index, index + 1);
+
+				//Tabs are expanded to the next tab stop, except in literals where it would change their value.
+				let mut column = 0;
+				for (index, c) in line.text.char_indices() {
+					if c == '\t' && line.mask[index] != Mask::String {
+						let width = TAB_SIZE - column % TAB_SIZE;
+						diagnostic = diagnostic.edit(line.offset + index, line.offset + index + 1, " ".repeat(width));
+						column += width;
+					}
+					else {
+						column += 1;
+					}
+				}
+				errors.push(diagnostic);
+			}
 		}
 
 		return errors;
@@ -72,17 +104,22 @@ impl TrailingWhiteSpace {
 }
 
 impl Rule for TrailingWhiteSpace {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"TrailingWhiteSpace"
+	}
 
-		for line in content.lines() {
-			match line.chars().last() {
-				Some(last_c) if last_c.is_whitespace() => errors.push(format!("[{}:{}]Trailing whitespace.",filename, line_number)),
-				_ => (),
-			}
+	fn description(&self) -> String {
+		String::from("No trailing whitespace.")
+	}
 
-			line_number += 1;
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+		for line in file.lines().iter() {
+			let end = line.text.trim_end().len();
+			if end != line.text.len() {
+				errors.push(Diagnostic::new(self.id(), file.filename, line.number, "Trailing whitespace.").columns(end, line.text.len())
+					.edit(line.offset + end, line.offset + line.text.len(), ""));
+			}
 		}
 
 		return errors;
@@ -107,64 +144,61 @@ impl IndentationLevel {
 		IndentationLevel { nb_spaces: nb_spaces_per_indentation_level }
 	}
 
-	fn verify_for_level(&self, filename: &str, lines: &mut Peekable<Lines>, indentation_type: IndentationType, indentation_level: usize,
-		errors: &mut Vec<String>, line_number: &mut usize)
+	fn verify_for_level(&self, filename: &str, lines: &mut Peekable<Iter<Line>>, indentation_type: IndentationType, indentation_level: usize,
+		errors: &mut Vec<Diagnostic>)
 	{
 		if indentation_type == IndentationType::OneLine {
-			*line_number += 1;
 			if let Some(line) = lines.next() {
-				let current_indentation = line.len() - line.trim_left().len();
+				let current_indentation = line.text.len() - line.text.trim_start().len();
 				if current_indentation != indentation_level * self.nb_spaces {
-					errors.push(format!("[{}:{}]Wrong indentation level. Expected {} whitespaces got {}",
-						filename, line_number, indentation_level * self.nb_spaces, current_indentation));
+					errors.push(Diagnostic::new(self.id(), filename, line.number, format!("Wrong indentation level. Expected {} whitespaces got {}", indentation_level * self.nb_spaces, current_indentation))
+						.columns(0, current_indentation));
 				}
 			}
 		}
 		else {
 			while let Some(line) = lines.next() {
-				*line_number += 1;
-
-				let current_indentation = line.len() - line.trim_left().len();
+				let current_indentation = line.text.len() - line.text.trim_start().len();
 				let needed_indentation = match indentation_type {
-					IndentationType::Basic if line.trim_left().starts_with('}') => (indentation_level - 1) * self.nb_spaces,
+					IndentationType::Basic if line.starts_with_punctuator("}") => indentation_level.saturating_sub(1) * self.nb_spaces,
 					_ => indentation_level * self.nb_spaces,
 				};
 
-				if line.len() != 0 && current_indentation != needed_indentation {
-					errors.push(format!("[{}:{}]Wrong indentation level. Expected {} whitespaces got {}",
-						filename, line_number, needed_indentation, current_indentation));
+				if !line.text.is_empty() && current_indentation != needed_indentation {
+					errors.push(Diagnostic::new(self.id(), filename, line.number, format!("Wrong indentation level. Expected {} whitespaces got {}", needed_indentation, current_indentation))
+						.columns(0, current_indentation));
 				}
 
 				let mut is_one_line = false;
-				if ["if ", "else", "switch ", "while ", "for "].iter().any(|x| line.trim_left().starts_with(x)) {
+				if ["if", "else", "switch", "while", "for"].iter().any(|x| line.starts_with_keyword(x)) {
 					if let Some(next_line) = lines.peek() {
-						if !next_line.trim_left().starts_with('{') {
+						if !next_line.starts_with_punctuator("{") {
 							is_one_line = true;
 						}
 					}
 				}
 				if is_one_line {
-					self.verify_for_level(filename, lines, IndentationType::OneLine, indentation_level + 1, errors, line_number);
+					self.verify_for_level(filename, lines, IndentationType::OneLine, indentation_level + 1, errors);
 				}
 
 				if indentation_type == IndentationType::Basic {
-					if line.trim_left().starts_with('}') {
+					if line.starts_with_punctuator("}") {
 						break;
 					}
-					else if line.trim_left().starts_with('{') {
-						self.verify_for_level(filename, lines, IndentationType::Basic, indentation_level + 1, errors, line_number);
+					else if line.starts_with_punctuator("{") {
+						self.verify_for_level(filename, lines, IndentationType::Basic, indentation_level + 1, errors);
 					}
-					else if ["case", "default"].iter().any(|x| line.trim_left().starts_with(x)) {
-						self.verify_for_level(filename, lines, IndentationType::SwitchCase, indentation_level + 1, errors, line_number);
+					else if ["case", "default"].iter().any(|x| line.starts_with_keyword(x)) {
+						self.verify_for_level(filename, lines, IndentationType::SwitchCase, indentation_level + 1, errors);
 					}
 				}
 				else if indentation_type == IndentationType::SwitchCase {
-					if line.trim_left().starts_with("break") {
+					if line.starts_with_keyword("break") {
 						break;
 					}
-					else if  line.trim_left().starts_with("return") {
+					else if line.starts_with_keyword("return") {
 						if let Some(next_line) = lines.peek() {
-							if !next_line.trim_left().starts_with("break") {
+							if !next_line.starts_with_keyword("break") {
 								break;
 							}
 						}
@@ -179,13 +213,20 @@ impl IndentationLevel {
 }
 
 impl Rule for IndentationLevel {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"IndentationLevel"
+	}
+
+	fn description(&self) -> String {
+		format!("The text between braces must be indented by {} spaces.", self.nb_spaces)
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 0;
 
-		let mut lines = content.lines().peekable();
+		let lines = file.lines();
 
-		self.verify_for_level(filename, &mut lines, IndentationType::Basic, 0, &mut errors, &mut line_number);
+		self.verify_for_level(file.filename, &mut lines.iter().peekable(), IndentationType::Basic, 0, &mut errors);
 
 		return errors;
 	}
@@ -205,36 +246,51 @@ impl Semicolon {
 
 //Expect IndentationLevel rule to be true
 impl Rule for Semicolon {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"Semicolon"
+	}
+
+	fn description(&self) -> String {
+		String::from("Semicolon must be followed by a newline and must not be preceded by a whitespace.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
-		for line in content.lines() {
-			match line.chars().filter(|x| *x == ';').count() {
-				n if n == 1 => {
-					if line.contains(" while") {
-						errors.push(format!("[{}:{}]Too much semicolon found on this line.", filename, line_number));
-					}
+		//Several statements on a line are left to OneStatementPerLine, semicolons of 'for' headers are not statements ends.
+		for line in file.lines().iter() {
+			let mut depth: usize = 0;
+			for (index, semicolon) in line.tokens.iter().enumerate() {
+				if semicolon.is_punctuator("(") {
+					depth += 1;
+				}
+				else if semicolon.is_punctuator(")") {
+					depth = depth.saturating_sub(1);
+				}
+				if !semicolon.is_punctuator(";") || depth > 0 {
+					continue;
+				}
 
-					if !line.ends_with(";") && !line.ends_with("\\"){
-						errors.push(format!("[{}:{}]Semicolon must be followed by a newline.", filename, line_number));
-					}
-					
-					let left_part = line.split(";").next().unwrap();
-					if !left_part.trim_right().is_empty() &&
-						left_part.trim_right().len() != left_part.len() {
-						errors.push(format!("[{}:{}]Semicolon must not be precedeed by whitespaces.", filename, line_number));
-					}
-				},
-				n if n >= 2 => {
-					if !line.contains(" for") || n > 2 {
-						errors.push(format!("[{}:{}]Too much semicolon found on this line.", filename, line_number));
+				let next = line.tokens.get(index + 1);
+				if next.is_some_and(|t| t.is_code() && !t.is_punctuator(";")) {
+					continue;
+				}
+				if semicolon.column + 1 != line.text.len() && !line.text.ends_with('\\') {
+					let mut diagnostic = Diagnostic::new(self.id(), file.filename, line.number, "Semicolon must be followed by a newline.").columns(semicolon.column, line.text.len());
+					if line.text[semicolon.column + 1..].trim().is_empty() {
+						diagnostic = diagnostic.edit(line.offset + semicolon.column + 1, line.offset + line.text.len(), "");
 					}
-				},
-				_ => {}
-			}
+					errors.push(diagnostic);
+				}
 
-			line_number += 1;
+				let left_part = &line.text[..semicolon.column];
+				if !left_part.trim_end().is_empty() &&
+					left_part.trim_end().len() != left_part.len() {
+					errors.push(Diagnostic::new(self.id(), file.filename, line.number, "Semicolon must not be precedeed by whitespaces.")
+						.columns(left_part.trim_end().len(), semicolon.column + 1)
+						.edit(line.offset + left_part.trim_end().len(), semicolon.offset, ""));
+				}
+			}
 		}
 
 		return errors;
@@ -254,35 +310,44 @@ impl Comma {
 }
 
 impl Rule for Comma {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"Comma"
+	}
 
-		for line in content.lines() {
-			let nb_comma = line.chars().filter(|x| *x == ',').count();
-			let mut actual_between = 0;
+	fn description(&self) -> String {
+		String::from("Comma must be followed by a single space, or a newline, and must not be preceded by a whitespace.")
+	}
 
-			if nb_comma <= 0 {
-				continue;
-			}
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
 
-			for between in line.split(",") {
-				if actual_between < nb_comma {
-					if actual_between != 0 && between.trim_left().len() + 1 != between.len() {
-						errors.push(format!("[{}:{}]Comma must be followed by exactly one whitespace.", filename, line_number));
-					}
-					if between.trim_right().len() != between.len() {
-						errors.push(format!("[{}:{}]Comma not must be precedeed by whitespaces.", filename, line_number))
+		for line in file.lines().iter() {
+			for comma in line.tokens.iter().filter(|t| t.is_punctuator(",")) {
+				let before = &line.text[..comma.column];
+				let after = &line.text[comma.column + 1..];
+
+				if after.trim().is_empty() {
+					if !after.is_empty() {
+						errors.push(Diagnostic::new(self.id(), file.filename, line.number, "The last comma must be followed by a newline.").columns(comma.column, line.text.len())
+							.edit(comma.end(), line.offset + line.text.len(), ""));
 					}
 				}
-				else if between.trim().is_empty() && !between.is_empty() {
-					errors.push(format!("[{}:{}]The last comma must be followed by a newline.", filename, line_number));
+				else if after.len() - after.trim_start().len() != 1 {
+					let next = line.text.len() - after.trim_start().len();
+					errors.push(Diagnostic::new(self.id(), file.filename, line.number, "Comma must be followed by exactly one whitespace.")
+						.columns(comma.column, next)
+						.edit(comma.end(), line.offset + next, " "));
 				}
 
-				actual_between += 1;
+				if before.trim_end().len() != before.len() {
+					let mut diagnostic = Diagnostic::new(self.id(), file.filename, line.number, "Comma not must be precedeed by whitespaces.")
+						.columns(before.trim_end().len(), comma.column + 1);
+					if !before.trim().is_empty() {
+						diagnostic = diagnostic.edit(line.offset + before.trim_end().len(), comma.offset, "");
+					}
+					errors.push(diagnostic);
+				}
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -303,57 +368,256 @@ impl StructureFieldsIndentation {
 
 //Expect Semicolon, OwnLineBrace and Trailing WhiteSpace rules to be true.
 impl Rule for StructureFieldsIndentation {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"StructureFieldsIndentation"
+	}
+
+	fn description(&self) -> String {
+		String::from("Structure and union fields must be aligned with the type name.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+		let lines = file.lines();
+
+		for declaration in file.declarations.iter() {
+			let aggregate = match declaration.aggregate() {
+				Some(aggregate) if !aggregate.keyword.is_keyword("enum") => aggregate,
+				_ => continue
+			};
+
+			//The tag, the last name of each field and the typedef alias must all start on the same column.
+			let mut words = Vec::new();
+			if let Some(ref tag) = aggregate.tag {
+				words.push((tag.line, tag.column));
+			}
+			for field in aggregate.fields.iter() {
+				if let Some(name) = field.names.last() {
+					words.push((name.line, word_column(&field.tokens, name)));
+				}
+			}
+			if let Declaration::Typedef(ref typedef) = *declaration {
+				if let Some(ref alias) = typedef.alias {
+					words.push((alias.line, word_column(&typedef.tokens, alias)));
+				}
+			}
 
-		let mut in_structure = false;
-		let mut have_typedef = false;
-		let mut level = 0;
-		let mut indentation = 0;
+			if let Some(&(_, indentation)) = words.first() {
+				for &(line, current_indentation) in words.iter().skip(1).filter(|&&(_, column)| column != indentation) {
+					errors.push(Diagnostic::new(self.id(), file.filename, line, format!("Wrong field indentation. Expected {} got {}.", indentation, current_indentation))
+						.columns(current_indentation, lines[line - 1].text.trim_end().len()));
+				}
+			}
+		}
+
+		return errors;
+	}
+}
 
+//Column of the group of tokens not separated by whitespaces that ends with the word (e.g. "*name").
+fn word_column(tokens: &[Token], word: &Token) -> usize {
+	let mut index = tokens.iter().position(|t| t == word).unwrap_or(0);
+
+	while index > 0 && tokens[index - 1].end() == tokens[index].offset {
+		index -= 1;
+	}
+
+	return tokens[index].column;
+}
 
-		for line in content.lines() {
-			if line.contains("{") {
-				level += 1;
+
+
+const ASSIGNMENT_OPERATORS: [&str; 11] = ["=", "+=", "-=", "*=", "/=", "%=", "&=", "^=", "|=", "<<=", ">>="];
+const BINARY_OPERATORS: [&str; 18] = ["+", "-", "*", "/", "%", "==", "!=", "<", ">", "<=", ">=", "&&", "||", "&", "|", "^", "<<", ">>"];
+//Also prefix operators when they don't follow an operand.
+const UNARY_OPERATORS: [&str; 4] = ["*", "&", "-", "+"];
+
+pub struct OperatorPadding {
+	
+}
+
+impl OperatorPadding {
+	pub fn new() -> OperatorPadding {
+		OperatorPadding { }
+	}
+}
+
+impl Rule for OperatorPadding {
+	fn id(&self) -> &'static str {
+		"OperatorPadding"
+	}
+
+	fn description(&self) -> String {
+		String::from("Binary, assignment and ternary operators must be padded by one space on the left and right.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+
+		let tokens: Vec<Token> = file.tokens.iter().filter(|t| t.is_code()).cloned().collect();
+		let stars = declaration_stars(file);
+		let types: Vec<&str> = file.declarations.iter().filter_map(|d| match *d {
+			Declaration::Typedef(ref typedef) => typedef.alias.map(|a| a.text),
+			_ => None
+		}).collect();
+		let mut conditionals = 0;
+		for index in 1..tokens.len().saturating_sub(1) {
+			let (previous, token, next) = (&tokens[index - 1], &tokens[index], &tokens[index + 1]);
+			if token.kind != TokenKind::Punctuator || token.preprocessor != next.preprocessor || token.preprocessor != previous.preprocessor {
+				continue;
 			}
-			if line.contains("}") && level > 0 {
-				level -= 1;
+			if [";", "{", "}"].contains(&token.text) {
+				conditionals = 0;
+				continue;
 			}
 
-			if level <= 0 && (line.contains("struct") || line.contains("union")) &&
-				!(line.contains("(") || line.contains(",") || line.contains(")") ||
-					line.trim_left().starts_with("//") || line.trim_left().starts_with("**")) {
-				//Too avoid been triggered in function declaration.
-				in_structure = true;
-				have_typedef = line.contains("typedef");
+			//"(a)-1" is a subtraction, "(t_size)-1" is not: it can't be fixed without knowing which one "a" is.
+			let mut fixable = true;
+			let is_operator = match token.text {
+				"?" => {
+					conditionals += 1;
+					true
+				},
+				//Not the one of a label, a case or a bit-field.
+				":" if conditionals > 0 => {
+					conditionals -= 1;
+					true
+				},
+				"*" if stars.contains(&token.offset) => false,
+				operator if UNARY_OPERATORS.contains(&operator) => {
+					let ends = ends_operand(&tokens, index - 1, &types);
+					fixable = ends.is_some();
+					ends != Some(false) && starts_operand(&tokens, index + 1)
+				},
+				operator => ASSIGNMENT_OPERATORS.contains(&operator) || BINARY_OPERATORS.contains(&operator)
+			};
+			if !is_operator {
+				continue;
 			}
 
-			if in_structure {
-				if !line.contains("{") && line.trim().len() > 0 && !(!have_typedef && line.contains("}")) && (!line.contains("typedef") || line.split_whitespace().count() > 2) {
-					//Indentation check is needed (else can be '{' or '};')
-					let current_indentation = line.len() - line.split_whitespace().last().unwrap().len();
-					if indentation <= 0 {
-						indentation = current_indentation;
-					}
-					else if current_indentation != indentation
-					{
-						errors.push(format!("[{}:{}]Wrong field indentation. Expected {} got {}.", filename, line_number, indentation, current_indentation));
+			//Either side may be a line break instead.
+			let (left, right) = (&file.content[previous.end()..token.offset], &file.content[token.end()..next.offset]);
+			let mut diagnostic = Diagnostic::new(self.id(), file.filename, token.line, format!("Operator '{}' must be padded by one space on both sides.", token.text))
+				.columns(token.column, token.column + token.text.len());
+			let mut padded = true;
+			for &(side, start, end) in [(left, previous.end(), token.offset), (right, token.end(), next.offset)].iter() {
+				if side != " " && !side.contains('\n') {
+					padded = false;
+					if side.trim().is_empty() && fixable {
+						diagnostic = diagnostic.edit(start, end, " ");
 					}
 				}
-				if line.contains("}") {
-					in_structure = false;
-					indentation = 0;
-				}
 			}
-
-			line_number += 1;
+			if !padded {
+				errors.push(diagnostic);
+			}
 		}
 
 		return errors;
 	}
 }
 
+//After a name, a literal, a closing parenthesis that isn't a cast, a closing bracket or a postfix operator. None when
+//it can't be told. The name of a macro and the parameters of a function-like one come before its replacement list,
+//which starts a new expression as in "#define NEG(x) -x".
+fn ends_operand(tokens: &[Token], index: usize, types: &[&str]) -> Option<bool> {
+	let token = &tokens[index];
+	return match token.kind {
+		TokenKind::Identifier if is_macro_name(tokens, index) => Some(false),
+		TokenKind::Identifier | TokenKind::Number | TokenKind::String | TokenKind::Character => Some(true),
+		TokenKind::Punctuator if token.text == "]" => Some(true),
+		TokenKind::Punctuator if token.text == ")" => {
+			let parameters = lexer::matching_open(tokens, index)
+				.is_some_and(|open| open > 0 && is_macro_name(tokens, open - 1) && tokens[open - 1].end() == tokens[open].offset);
+			if parameters { Some(false) } else { is_cast(tokens, index, types).map(|cast| !cast) }
+		},
+		TokenKind::Punctuator if token.text == "++" || token.text == "--" => {
+			if index > 0 { ends_operand(tokens, index - 1, types) } else { Some(false) }
+		},
+		_ => Some(false)
+	};
+}
+
+fn is_macro_name(tokens: &[Token], index: usize) -> bool {
+	return index > 0 && tokens[index].kind == TokenKind::Identifier && tokens[index - 1].directive() == "define";
+}
+
+//Before anything but a closing parenthesis or a separator, stars included as in "(t_list **)".
+fn starts_operand(tokens: &[Token], index: usize) -> bool {
+	let next = tokens[index..].iter().find(|t| !t.is_punctuator("*"));
+	return next.is_some_and(|t| ![")", "]", ",", ";"].iter().any(|p| t.is_punctuator(p)));
+}
+
+//Parentheses holding a type name: "(char)", "(const char *)", "(t_list *)", "(size_t)". None for a single name that
+//isn't known to be a type, as in "(a)".
+fn is_cast(tokens: &[Token], close: usize, types: &[&str]) -> Option<bool> {
+	let open = match lexer::matching_open(tokens, close) {
+		Some(open) => open,
+		None => return Some(false)
+	};
+	//The parentheses of a call, of 'sizeof' or of a control structure.
+	if open > 0 && (tokens[open - 1].kind == TokenKind::Identifier || tokens[open - 1].kind == TokenKind::Keyword
+		|| tokens[open - 1].is_punctuator(")") || tokens[open - 1].is_punctuator("]")) {
+		return Some(false);
+	}
+	let inside = &tokens[open + 1..close];
+	return match inside.first() {
+		Some(first) if first.kind == TokenKind::Keyword => Some(parser::DECLARATION_KEYWORDS.contains(&first.text)),
+		Some(first) if first.kind == TokenKind::Identifier && inside[1..].iter().all(|t| t.is_punctuator("*")) => {
+			let is_type = types.contains(&first.text) || first.text.starts_with("t_") || first.text.ends_with("_t");
+			if is_type || inside.len() > 1 { Some(true) } else { None }
+		},
+		_ => Some(false)
+	};
+}
+
+//Offsets of the stars declaring pointers, as in "t_list *node", outside of initializers and array sizes.
+fn declaration_stars(file: &SourceFile) -> Vec<usize> {
+	let mut declarations: Vec<&[Token]> = Vec::new();
+	for declaration in file.declarations.iter() {
+		let locals = match *declaration {
+			Declaration::Function(ref function) => {
+				declarations.push(&function.return_type);
+				declarations.extend(function.parameters.iter().map(|p| &p.tokens[..]));
+				declarations.extend(function.locals.iter().map(|l| &l.tokens[..]));
+				&function.locals[..]
+			},
+			Declaration::Variable(ref variable) => {
+				declarations.push(&variable.tokens);
+				&[]
+			},
+			Declaration::Typedef(ref typedef) => {
+				declarations.push(&typedef.tokens);
+				&[]
+			},
+			Declaration::Aggregate(_) => &[]
+		};
+		let aggregates = declaration.aggregate().into_iter().chain(locals.iter().filter_map(|l| l.aggregate.as_ref()));
+		for aggregate in aggregates {
+			declarations.extend(aggregate.fields.iter().map(|f| &f.tokens[..]));
+		}
+	}
+
+	let mut stars = Vec::new();
+	for tokens in declarations {
+		for declarator in parser::split(tokens, ",") {
+			let end = declarator.iter().position(|t| t.is_punctuator("=")).unwrap_or(declarator.len());
+			let mut index = 0;
+			while index < end {
+				if declarator[index].is_punctuator("[") {
+					index = lexer::matching_close(declarator, index).unwrap_or(end);
+				}
+				else if declarator[index].is_punctuator("*") {
+					stars.push(declarator[index].offset);
+				}
+				index += 1;
+			}
+		}
+	}
+
+	return stars;
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -380,6 +644,9 @@ mod tests {
 		assert_eq!(space_indentation.verify("", "12345\t\t\n12345").len(), 1);
 		assert_eq!(space_indentation.verify("", "12345\t\n1\t345\n12345").len(), 2);
 		assert_eq!(space_indentation.verify("", "123456\n12\\t45\n123456").len(), 0);
+
+		assert_eq!(space_indentation.fix("", "\tint\ta;\n"), "        int     a;\n");
+		assert_eq!(space_indentation.fix("", "\tputs(\"\t\");"), "        puts(\"\t\");");
 	}
 
 	#[test]
@@ -392,6 +659,8 @@ mod tests {
 		assert_eq!(trailing_whitespace.verify("", "  \n \n").len(), 2);
 		assert_eq!(trailing_whitespace.verify("", " z\t\t").len(), 1);
 		assert_eq!(trailing_whitespace.verify("", " aad\t\n  \n \t").len(), 3);
+
+		assert_eq!(trailing_whitespace.fix("", "a  \nb\t\nc"), "a\nb\nc");
 	}
 
 	#[test]
@@ -429,10 +698,14 @@ mod tests {
 		assert_eq!(semicolon.verify("", "    for ( ; ;)").len(), 0);
 		assert_eq!(semicolon.verify("", "#define MACRO(something) \\\n do_something();   \\\n other_things();").len(), 0);
 
-		assert_ne!(semicolon.verify("", " for (;;);").len(), 0);
+		assert_eq!(semicolon.verify("", "do\n{\n} while (a);").len(), 0);
 		assert_ne!(semicolon.verify("", " return ;").len(), 0);
 		assert_ne!(semicolon.verify("", ";;;").len(), 0);
 		assert_ne!(semicolon.verify("", ";\t").len(), 0);
+		assert_ne!(semicolon.verify("", "a; /* b */").len(), 0);
+
+		assert_eq!(semicolon.fix("", "a = 1 ;  \nb;"), "a = 1;\nb;");
+		assert_eq!(semicolon.fix("", "a; b;"), "a; b;");
 	}
 
 	#[test]
@@ -443,6 +716,7 @@ mod tests {
 		assert_eq!(comma.verify("", "something, something else\nother").len(), 0);
 		assert_eq!(comma.verify("", "comma, comma, comma").len(), 0);
 		assert_eq!(comma.verify("", "{\n    {\n    \n}\n}").len(), 0);
+		assert_eq!(comma.verify("", "c = ',';\ns = \"a,b\";").len(), 0);
 
 		assert_ne!(comma.verify("", "something, \nother").len(), 0);
 		assert_ne!(comma.verify("", "something,\t\nother").len(), 0);
@@ -450,6 +724,8 @@ mod tests {
 		assert_ne!(comma.verify("", "comma ,comma ,comma").len(), 0);
 		assert_ne!(comma.verify("", "comma , comma , comma").len(), 0);
 		assert_ne!(comma.verify("", "comma,comma,comma").len(), 0);  
+
+		assert_eq!(comma.fix("", "f(a ,b,   c, \n  d);"), "f(a, b, c,\n  d);");
 	}
 
 	#[test]
@@ -468,4 +744,29 @@ mod tests {
 		assert_eq!(structure_fields_indentation.verify("", "typedef struct test\n{\n           int arg1;\n}     s_test;\n").len(), 1);
 		assert_eq!(structure_fields_indentation.verify("", "typedef struct\n{\n    int arg1;\n} s_test;\n").len(), 1);
 	}
+
+	#[test]
+	fn operator_padding() {
+		let operator_padding = OperatorPadding::new();
+
+		assert_eq!(operator_padding.verify("", "#include <stdio.h>\nint f(t_list *list, char **argv)\n{\n    t_list  *node = *argv;\n    int     a[N * 2];\n\n    a = -b + *c - &d;\n    node->next = s.x++ * 2;\n    return (a ? (char *)b : -1);\n}").len(), 0);
+		assert_eq!(operator_padding.verify("", "int f(void)\n{\n    a = (char)*b + (t_list **)c;\n    a = b\n        + c;\n    switch (a)\n    {\n        case -1:\n            i++;\n    }\n}").len(), 0);
+		assert_eq!(operator_padding.verify("", "struct s_a\n{\n    int a : 3;\n    int *b;\n};\n#define F(a) (-(a) * 2)").len(), 0);
+
+		assert_eq!(operator_padding.verify("", "a=b;").len(), 1);
+		assert_eq!(operator_padding.verify("", "a = b+c  * d;").len(), 2);
+		assert_eq!(operator_padding.verify("", "a = b?c: d;").len(), 2);
+		assert_eq!(operator_padding.verify("", "a += b&c;\nx = y  <<z;").len(), 2);
+
+		assert_eq!(operator_padding.fix("", "a=b*c ? d:-e;"), "a = b * c ? d : -e;");
+		assert_eq!(operator_padding.fix("", "a  =b /* c */+ d;"), "a = b /* c */+ d;");
+
+		assert_eq!(operator_padding.verify("", "typedef int my_int;\nint f(void)\n{\n    a = (size_t)-1 + (t_list)*p + (my_int)-b;\n    a = sizeof (t_list) * 2 + f(a) - (a) - 1;\n}").len(), 0);
+		assert_eq!(operator_padding.verify("", "a = (b)-1;").len(), 1);
+		assert_eq!(operator_padding.fix("", "a = (b)-1;"), "a = (b)-1;");
+
+		let macros = "#define MINUS_ONE -1\n#define NEG(x) -x\n# define ERROR -1";
+		assert_eq!(operator_padding.verify("", macros).len(), 0);
+		assert_eq!(operator_padding.fix("", macros), macros);
+	}
 }
@@ -1,4 +1,10 @@
 use rules::Rule;
+use diagnostic::Diagnostic;
+use source::SourceFile;
+use lexer;
+use lexer::{Token, TokenKind};
+use parser;
+use parser::{Aggregate, Declaration};
 
 
 
@@ -13,24 +19,30 @@ impl OwnLineBrace {
 }
 
 impl Rule for OwnLineBrace {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"OwnLineBrace"
+	}
+
+	fn description(&self) -> String {
+		String::from("All braces must be on their own line.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;		
 
-		for line in content.lines() {
-			if line.contains("{") && !(line.trim().len() == 1 ||
-				(line.chars().filter(|c| !c.is_whitespace()).count() == 2 && line.trim_right().ends_with("\\"))) {
-				errors.push(format!("[{}:{}]Opening brace must be on their own line.", filename, line_number));
-			}
-			else if line.contains("}") && !(line.trim().len() == 1 ||
-					(line.chars().filter(|c| !c.is_whitespace()).count() == 2 && line.trim_right().ends_with("\\"))) {
+		for line in file.lines().iter() {
+			let alone = line.tokens.len() == 1;
 
-				if !line.ends_with(";") {
-					errors.push(format!("[{}:{}]Closing brace must be on their own line.", filename, line_number));
+			if let Some(brace) = line.tokens.iter().find(|t| t.is_punctuator("{")).filter(|_| !alone) {
+				errors.push(Diagnostic::new(self.id(), file.filename, line.number, "Opening brace must be on their own line.")
+					.columns(brace.column, brace.column + 1));
+			}
+			else if let Some(brace) = line.tokens.iter().find(|t| t.is_punctuator("}")).filter(|_| !alone) {
+				if !line.tokens.last().unwrap().is_punctuator(";") {
+					errors.push(Diagnostic::new(self.id(), file.filename, line.number, "Closing brace must be on their own line.")
+						.columns(brace.column, brace.column + 1));
 				}
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -49,50 +61,53 @@ impl MultiLinesComment {
 }
 
 impl Rule for MultiLinesComment {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"MultiLinesComment"
+	}
+
+	fn description(&self) -> String {
+		String::from("Multi-line comment delimiters must be on their own line, and intermediary lines must start with '**'.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
-		
-		let mut in_comment = false;
-
-		for line in content.lines() {
-			if line.contains("*/") && !line.contains("/*") {
-				//Multi lines comment
-				if !in_comment {
-					errors.push(format!("[{}:{}]Unexpected comment end delimiter.", filename, line_number));
-				}
+		let tokens = &file.tokens;
+		let lines: Vec<&str> = file.content.lines().collect();
 
-				if line.chars().filter(|c| !c.is_whitespace()).count() != 2 {
-					errors.push(format!("[{}:{}]Comment end delimiter must appear on its own line.", filename, line_number));
-				}
-				in_comment = false;
+		for (index, token) in tokens.iter().enumerate() {
+			if token.is_punctuator("*") && tokens.get(index + 1).is_some_and(|next| next.is_punctuator("/") && next.offset == token.end()) {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Unexpected comment end delimiter.").columns(token.column, token.column + 2));
 			}
 
-			if in_comment {
-				if !line.trim_left().starts_with("**") {
-					errors.push(format!("[{}:{}]Comment intermediary line must start with '**'.", filename, line_number));
-				}
+			if token.kind != TokenKind::Comment || !token.text.starts_with("/*") {
+				continue;
 			}
 
-			if line.contains("/*") && !line.contains("*/") {
-				//Multi lines comment
-				if in_comment {
-					errors.push(format!("[{}:{}]Comments can't be nested.", filename, line_number));
-				}
+			if token.text[2..].contains("/*") {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Comments can't be nested."));
+			}
 
-				let nb_non_white_space = line.chars().filter(|c| !c.is_whitespace()).count();
-				if !(nb_non_white_space == 2 || (nb_non_white_space == 3 && line.contains("/**"))) {
-					errors.push(format!("[{}:{}]Comment start delimiter must appear on its own line.", filename, line_number));
-				}
-				in_comment = true;
+			let end_line = token.end_line();
+			if end_line == token.line {
+				continue;//Single line comment.
+			}
 
+			if !["/*", "/**"].contains(&lines[token.line - 1].trim()) {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Comment start delimiter must appear on its own line."));
 			}
 
-			line_number += 1;
-		}
+			for line_number in token.line + 1..end_line {
+				if !lines[line_number - 1].trim_start().starts_with("**") {
+					errors.push(Diagnostic::new(self.id(), file.filename, line_number, "Comment intermediary line must start with '**'."));
+				}
+			}
 
-		if in_comment {
-			errors.push(format!("[{}:{}]Expected comment end delimiter.", filename, line_number));
+			if !token.text.ends_with("*/") {
+				errors.push(Diagnostic::new(self.id(), file.filename, end_line + 1, "Expected comment end delimiter."));
+			}
+			else if lines[end_line - 1].trim() != "*/" {
+				errors.push(Diagnostic::new(self.id(), file.filename, end_line, "Comment end delimiter must appear on its own line."));
+			}
 		}
 
 		return errors;
@@ -112,16 +127,19 @@ impl Goto {
 }
 
 impl Rule for Goto {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"Goto"
+	}
 
-		for line in content.lines() {
-			if line.contains("goto") {
-				errors.push(format!("[{}:{}]Goto statement unauthorized.", filename, line_number));
-			}
+	fn description(&self) -> String {
+		String::from("The goto statement must not be used.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
 
-			line_number += 1;
+		for token in file.tokens.iter().filter(|t| t.is_keyword("goto")) {
+			errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Goto statement unauthorized.").columns(token.column, token.column + token.text.len()));
 		}
 
 		return errors;
@@ -141,33 +159,56 @@ impl Enum {
 }
 
 impl Rule for Enum {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"Enum"
+	}
+
+	fn description(&self) -> String {
+		String::from("Enumeration values must be entirely capitalized and on their own line.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+		let tokens = &file.tokens;
+		let code: Vec<Token> = tokens.iter().filter(|t| t.is_code()).cloned().collect();
+
+		for (index, token) in code.iter().enumerate() {
+			if !token.is_keyword("enum") {
+				continue;
+			}
 
-		let mut in_enum = false;
+			let mut open = index + 1;
+			if code.get(open).is_some_and(|t| t.kind == TokenKind::Identifier) {
+				open += 1;
+			}
+			if !code.get(open).is_some_and(|t| t.is_punctuator("{")) {
+				continue;//Not an enum definition.
+			}
+			let close = lexer::matching_close(&code, open).unwrap_or(code.len());
 
-		for line in content.lines() {
-			if in_enum {
-				if line.contains("}") {
-					in_enum = false;
+			let mut depth = 0;
+			let mut expect_value = true;
+			for (position, value) in code.iter().enumerate().take(close).skip(open + 1) {
+				if ["(", "[", "{"].iter().any(|p| value.is_punctuator(p)) {
+					depth += 1;
 				}
-				if in_enum && line.to_uppercase() != line {
-					errors.push(format!("[{}:{}]Enum values must be entirely capitalized. Expected '{}' got '{}'", filename, line_number, line.to_uppercase(), line));
+				else if [")", "]", "}"].iter().any(|p| value.is_punctuator(p)) {
+					depth -= 1;
 				}
-				if line.contains(",") &&
-					line.trim().len() > 1 &&//To prevent having only a comma on a line.
-					!line.split(",").last().unwrap().trim().is_empty() {
-					errors.push(format!("[{}:{}]Enum values must be on their own line.", filename, line_number));
+				else if depth == 0 && value.is_punctuator(",") {
+					expect_value = true;
+					if code.get(position + 1).is_some_and(|next| position + 1 < close && next.line == value.line) {
+						errors.push(Diagnostic::new(self.id(), file.filename, value.line, "Enum values must be on their own line.").columns(value.column, value.column + 1));
+					}
+				}
+				else if expect_value && value.kind == TokenKind::Identifier {
+					expect_value = false;
+					if value.text.to_uppercase() != value.text {
+						errors.push(Diagnostic::new(self.id(), file.filename, value.line, format!("Enum values must be entirely capitalized. Expected '{}' got '{}'", value.text.to_uppercase(), value.text))
+							.columns(value.column, value.column + value.text.len()));
+					}
 				}
 			}
-
-			//Start_with '**' -> multilines comments intermediary lines.
-			if line.contains("enum") && !line.trim_left().starts_with("//") && !line.trim_left().starts_with("**") {
-				in_enum = true;
-			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -187,22 +228,412 @@ impl StaticVariable {
 }
 
 impl Rule for StaticVariable {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"StaticVariable"
+	}
+
+	fn description(&self) -> String {
+		String::from("Static variables must be constant.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+		let tokens = &file.tokens;
+		let code: Vec<Token> = tokens.iter().filter(|t| t.is_code() && !t.preprocessor).cloned().collect();
+
+		for (index, token) in code.iter().enumerate() {
+			if !token.is_keyword("static") {
+				continue;
+			}
+
+			let mut is_const = false;
+			let mut position = index + 1;
+			while let Some(next) = code.get(position) {
+				if next.is_keyword("const") {
+					is_const = true;
+				}
+				else if next.is_punctuator("{") {
+					//Structure, union or enum definition.
+					position = lexer::matching_close(&code, position).unwrap_or(code.len());
+				}
+				else if next.is_punctuator("(") {
+					is_const = true;//Function.
+					break;
+				}
+				else if [";", "=", ","].iter().any(|p| next.is_punctuator(p)) {
+					break;
+				}
+				position += 1;
+			}
+
+			if !is_const {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, "Static variable must be const.").columns(token.column, token.column + token.text.len()));
+			}
+		}
+
+		return errors;
+	}
+}
+
+
+
+pub struct PointerSymbol {
+	
+}
+
+impl PointerSymbol {
+	pub fn new() -> PointerSymbol {
+		PointerSymbol { }
+	}
+
+	//Stars followed by the declared name, or a qualifier, must be stuck to it and to each other, and separated from
+	//the type by a whitespace: "char *p", "char **argv", "void (*f)(int)". The name of aligned declarations, fields
+	//and locals, is kept on its column by the fix.
+	fn check_declarator(&self, file: &SourceFile, tokens: &[Token], aligned: bool, errors: &mut Vec<Diagnostic>) {
+		let mut index = 0;
+		while index < tokens.len() {
+			let token = &tokens[index];
+			if token.is_punctuator("=") || token.is_punctuator(":") {
+				//Initializer or bit-field width, up to the next declarator.
+				let mut depth: usize = 0;
+				while index < tokens.len() && !(depth == 0 && tokens[index].is_punctuator(",")) {
+					if ["(", "[", "{"].iter().any(|p| tokens[index].is_punctuator(p)) {
+						depth += 1;
+					}
+					else if [")", "]", "}"].iter().any(|p| tokens[index].is_punctuator(p)) {
+						depth = depth.saturating_sub(1);
+					}
+					index += 1;
+				}
+				continue;
+			}
+			else if token.is_punctuator("[") || token.is_punctuator("{") {
+				index = lexer::matching_close(tokens, index).unwrap_or(tokens.len());
+			}
+			else if token.is_punctuator("*") && index > 0 {
+				let end = index + tokens[index..].iter().take_while(|t| t.is_punctuator("*")).count();
+				let (previous, next) = (&tokens[index - 1], tokens.get(end));
+				let next = match next {
+					Some(next) if next.kind == TokenKind::Identifier || ["const", "volatile", "restrict"].iter().any(|k| next.is_keyword(k)) => next,
+					_ => {
+						index = end;
+						continue;
+					}
+				};
+
+				let attached = tokens[index..=end].windows(2).all(|pair| pair[0].end() == pair[1].offset);
+				let separated = token.offset > previous.end() || previous.is_punctuator("(");
+				if !attached || !separated {
+					let columns = if next.line == token.line { (token.column, next.column) } else { (token.column, token.column + 1) };
+					let mut error = Diagnostic::new(self.id(), file.filename, token.line, "Pointer symbol must be next to the name, not the type.")
+						.columns(columns.0, columns.1);
+
+					//Only rewritten when nothing but spaces are moved, on a single line.
+					let between = &file.content[previous.end()..next.offset];
+					if previous.line == next.line && between.chars().all(|c| c == '*' || c == ' ' || c == '\t') {
+						let leading = &file.content[previous.end()..token.offset];
+						let stars = "*".repeat(end - index);
+						let replacement = if previous.is_punctuator("(") {
+							format!("{}{}", leading, stars)
+						}
+						else if aligned {
+							format!("{}{}", " ".repeat(between.len().saturating_sub(stars.len()).max(1)), stars)
+						}
+						else {
+							format!("{}{}", if separated { leading } else { " " }, stars)
+						};
+						error = error.edit(previous.end(), next.offset, replacement);
+					}
+					errors.push(error);
+				}
+				index = end;
+				continue;
+			}
+			index += 1;
+		}
+	}
+
+	fn check_aggregate(&self, file: &SourceFile, aggregate: Option<&Aggregate>, errors: &mut Vec<Diagnostic>) {
+		if let Some(aggregate) = aggregate.filter(|a| !a.keyword.is_keyword("enum")) {
+			for field in aggregate.fields.iter() {
+				self.check_declarator(file, &field.tokens, true, errors);
+			}
+		}
+	}
+}
+
+impl Rule for PointerSymbol {
+	fn id(&self) -> &'static str {
+		"PointerSymbol"
+	}
+
+	fn description(&self) -> String {
+		String::from("The pointer symbol '*' must appear next to the variable name, not the type.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+
+		for declaration in file.declarations.iter() {
+			self.check_aggregate(file, declaration.aggregate(), &mut errors);
+			match *declaration {
+				Declaration::Function(ref function) => {
+					let mut head = function.return_type.clone();
+					head.push(function.name);
+					self.check_declarator(file, &head, false, &mut errors);
+					for parameter in function.parameters.iter() {
+						self.check_declarator(file, &parameter.tokens, false, &mut errors);
+					}
+					for local in function.locals.iter() {
+						self.check_aggregate(file, local.aggregate.as_ref(), &mut errors);
+						self.check_declarator(file, &local.tokens, true, &mut errors);
+					}
+				},
+				Declaration::Typedef(ref typedef) => self.check_declarator(file, &typedef.tokens, false, &mut errors),
+				Declaration::Variable(ref variable) => self.check_declarator(file, &variable.tokens, false, &mut errors),
+				Declaration::Aggregate(_) => {}
+			}
+		}
+		errors.sort_by_key(|e| (e.line, e.columns));
+
+		return errors;
+	}
+}
+
+
+pub struct OneDeclarationPerLine {
+	
+}
+
+impl OneDeclarationPerLine {
+	pub fn new() -> OneDeclarationPerLine {
+		OneDeclarationPerLine { }
+	}
+
+	//The commas separating declarators aren't nested, unlike those of parameter lists and initializers.
+	fn check_declaration(&self, file: &SourceFile, tokens: &[Token], name: Option<&Token>, errors: &mut Vec<Diagnostic>) {
+		let declarators = parser::split(tokens, ",");
+		if declarators.len() < 2 {
+			return;
+		}
+		let comma = &tokens[declarators[0].len()];
+		let mut error = Diagnostic::new(self.id(), file.filename, comma.line, "One declaration per line.").columns(comma.column, comma.column + 1);
+		if let Some(replacement) = name.and_then(|name| split_declaration(file.content, tokens, &declarators, name)) {
+			error = error.edit(declarators[0].last().unwrap().end(), tokens.last().unwrap().end(), replacement);
+		}
+		errors.push(error);
+	}
+
+	fn check_aggregate(&self, file: &SourceFile, aggregate: Option<&Aggregate>, errors: &mut Vec<Diagnostic>) {
+		//Enumerators are left to Enum.
+		if let Some(aggregate) = aggregate.filter(|a| !a.keyword.is_keyword("enum")) {
+			for field in aggregate.fields.iter() {
+				self.check_declaration(file, &field.tokens, field.names.first(), errors);
+			}
+		}
+	}
+}
+
+impl Rule for OneDeclarationPerLine {
+	fn id(&self) -> &'static str {
+		"OneDeclarationPerLine"
+	}
+
+	fn description(&self) -> String {
+		String::from("One declaration per line, in functions, structures and unions.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
-		for line in content.lines() {
-			if line.trim_left().starts_with("static ") && (!line.contains("(") || line.contains("=")) && !line.contains("static const") {
-				errors.push(format!("[{}:{}]Static variable must be const.", filename, line_number));
+		for declaration in file.declarations.iter() {
+			self.check_aggregate(file, declaration.aggregate(), &mut errors);
+			match *declaration {
+				Declaration::Function(ref function) => {
+					for local in function.locals.iter() {
+						self.check_aggregate(file, local.aggregate.as_ref(), &mut errors);
+						self.check_declaration(file, &local.tokens, local.names.first(), &mut errors);
+					}
+				},
+				Declaration::Variable(ref variable) => self.check_declaration(file, &variable.tokens, variable.names.first(), &mut errors),
+				_ => {}
+			}
+		}
+		errors.sort_by_key(|e| (e.line, e.columns));
+
+		return errors;
+	}
+}
+
+//Each declarator on its own line, after the type of the first one: "int a, *b;" becomes "int a;\nint *b;". The names
+//stay aligned on the column of the first one. None when it would lose comments, or when a structure is defined.
+fn split_declaration(content: &str, tokens: &[Token], declarators: &[&[Token]], name: &Token) -> Option<String> {
+	let first = declarators[0];
+	let line_start = content[..tokens[0].offset].rfind('\n').map_or(0, |i| i + 1);
+	let indentation = &content[line_start..tokens[0].offset];
+	let is_definition = tokens.windows(2).any(|w| w[1].is_punctuator("{") && !["=", ",", "{"].iter().any(|p| w[0].is_punctuator(p)));
+	if !indentation.chars().all(|c| c == ' ' || c == '\t') || is_definition || tokens.windows(2).any(|w| !content[w[0].end()..w[1].offset].trim().is_empty()) {
+		return None;
+	}
+
+	let name = first.iter().position(|t| t.offset == name.offset)?;
+	let start = first[..name].iter().rposition(|t| !t.is_punctuator("*") && !t.is_punctuator("(")).map_or(0, |i| i + 1);
+	if start == 0 {
+		return None;
+	}
+	let stars = |declarator: &[Token]| declarator.iter().take_while(|t| t.is_punctuator("*")).count();
+	let type_name = &content[tokens[0].offset..first[start].offset];
+	let base = type_name.trim_end();
+	let width = type_name.len() + stars(&first[start..]);
+
+	let mut replacement = String::new();
+	for declarator in declarators[1..].iter() {
+		let (start, end) = (declarator.first()?.offset, declarator.last()?.end());
+		let padding = width.saturating_sub(base.len() + stars(declarator)).max(1);
+		replacement += &format!(";\n{}{}{}{}", indentation, base, " ".repeat(padding), &content[start..end]);
+	}
+
+	return Some(replacement);
+}
+
+
+
+pub struct OneStatementPerLine {
+	
+}
+
+impl OneStatementPerLine {
+	pub fn new() -> OneStatementPerLine {
+		OneStatementPerLine { }
+	}
+
+	fn check_statements(&self, file: &SourceFile, tokens: &[Token], errors: &mut Vec<Diagnostic>) {
+		//Expressions joined by commas are statements too, unless they declare variables.
+		let bodies: Vec<(usize, usize)> = file.functions().filter_map(|f| f.body.map(|(open, close)| (open.offset, close.offset))).collect();
+		let locals: Vec<usize> = file.functions().flat_map(|f| f.locals.iter().map(|l| l.tokens[0].offset)).collect();
+		let mut parts = Vec::new();
+		for statement in statements(tokens) {
+			match statement {
+				Some(statement) if is_expression(statement, &bodies, &locals) => {
+					parts.extend(parser::split(statement, ",").into_iter().filter(|p| !p.is_empty()).map(Some));
+				},
+				statement => parts.push(statement)
 			}
+		}
 
-			line_number += 1;
+		for pair in parts.windows(2) {
+			if let (Some(previous), Some(statement)) = (pair[0], pair[1]) {
+				let (first, last) = (&statement[0], &statement[statement.len() - 1]);
+				if previous.last().is_some_and(|p| p.end_line() == first.line) {
+					let end = if last.line == first.line { last.column + last.text.len() } else { first.column + first.text.len() };
+					errors.push(Diagnostic::new(self.id(), file.filename, first.line, "One statement per line.").columns(first.column, end));
+				}
+			}
 		}
+	}
+}
+
+impl Rule for OneStatementPerLine {
+	fn id(&self) -> &'static str {
+		"OneStatementPerLine"
+	}
+
+	fn description(&self) -> String {
+		String::from("One statement per line, the body of a control structure starting on the line after it.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+
+		let code: Vec<Token> = file.tokens.iter().filter(|t| t.is_code() && !t.preprocessor).cloned().collect();
+		self.check_statements(file, &code, &mut errors);
+
+		//The body of each macro, after its name and parameters.
+		let directives = file.tokens.iter().enumerate().filter(|&(_, t)| t.kind == TokenKind::Preprocessor && t.directive() == "define");
+		for (index, _) in directives {
+			let macro_tokens: Vec<Token> = file.tokens[index + 1..].iter().take_while(|t| t.preprocessor && t.kind != TokenKind::Preprocessor)
+				.filter(|t| t.is_code()).cloned().collect();
+			let mut start = 1.min(macro_tokens.len());
+			if macro_tokens.get(1).is_some_and(|t| t.is_punctuator("(") && t.offset == macro_tokens[0].end()) {
+				start = lexer::matching_close(&macro_tokens, 1).map_or(macro_tokens.len(), |close| close + 1);
+			}
+			self.check_statements(file, &macro_tokens[start..], &mut errors);
+		}
+		errors.sort_by_key(|e| (e.line, e.columns));
 
 		return errors;
 	}
 }
 
+//Statements and control structure headers ("if (x)", "else", "do", "case X:") in order, None for the braces between them.
+fn statements<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<Option<&'t [Token<'a>]>> {
+	let mut statements = Vec::new();
+	let is_header = |token: &Token| ["if", "while", "for", "switch"].iter().any(|k| token.is_keyword(k));
+
+	let mut start = 0;
+	let mut index = 0;
+	while index < tokens.len() {
+		let token = &tokens[index];
+		if token.is_punctuator(";") {
+			statements.push(Some(&tokens[start..index + 1]));
+			start = index + 1;
+		}
+		else if token.is_punctuator("{") {
+			//Initializers belong to the statement, other braces open a block.
+			if tokens[start..index].iter().any(|t| t.is_punctuator("=")) {
+				index = lexer::matching_close(tokens, index).unwrap_or(tokens.len() - 1);
+			}
+			else {
+				statements.push(None);
+				start = index + 1;
+			}
+		}
+		else if token.is_punctuator("}") {
+			statements.push(None);
+			start = index + 1;
+		}
+		else if token.is_punctuator("(") || token.is_punctuator("[") {
+			let open = index;
+			index = lexer::matching_close(tokens, index).unwrap_or(tokens.len() - 1);
+			let keyword = if tokens[start].is_keyword("else") { start + 1 } else { start };
+			//The 'while' of a 'do' ends with a semicolon instead of a body.
+			let is_do_while = tokens[keyword].is_keyword("while") && tokens.get(index + 1).is_some_and(|t| t.is_punctuator(";"));
+			if open == keyword + 1 && is_header(&tokens[keyword]) && !is_do_while {
+				statements.push(Some(&tokens[start..index + 1]));
+				start = index + 1;
+			}
+		}
+		else if (token.is_keyword("else") && !tokens.get(index + 1).is_some_and(|t| t.is_keyword("if"))) || token.is_keyword("do") {
+			statements.push(Some(&tokens[start..index + 1]));
+			start = index + 1;
+		}
+		else if (token.is_keyword("case") || token.is_keyword("default")) && start == index {
+			//Up to the colon, which is not the one of a conditional operator.
+			let mut conditionals = 0;
+			while index + 1 < tokens.len() && !(tokens[index].is_punctuator(":") && conditionals == 0) {
+				if tokens[index].is_punctuator("?") {
+					conditionals += 1;
+				}
+				else if tokens[index].is_punctuator(":") {
+					conditionals -= 1;
+				}
+				index += 1;
+			}
+			statements.push(Some(&tokens[start..index + 1]));
+			start = index + 1;
+		}
+		index += 1;
+	}
+
+	return statements;
+}
+
+//A statement of a function body that isn't the declaration of a local variable.
+fn is_expression(statement: &[Token], bodies: &[(usize, usize)], locals: &[usize]) -> bool {
+	let offset = statement[0].offset;
+	return bodies.iter().any(|&(open, close)| open < offset && offset < close) && !locals.contains(&offset);
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -247,7 +678,12 @@ mod tests {
 		assert_eq!(goto.verify("", "zdnkcndccc").len(), 0);
 		assert_eq!(goto.verify("", "go\nto\ngo\nto\n").len(), 0);
 		assert_eq!(goto.verify("", "goto").len(), 1);
-		assert_eq!(goto.verify("", "goto\nadezf\nvvrgotoded").len(), 2);
+		assert_eq!(goto.verify("", "goto\nadezf\nvvrgotoded").len(), 1);
+		assert_eq!(goto.verify("", "gotoxy(0, 0);\n\"goto\";\n//goto").len(), 0);
+
+		let errors = goto.verify("main.c", "\n  goto end;");
+		assert_eq!((errors[0].rule, errors[0].filename.as_str()), ("Goto", "main.c"));
+		assert_eq!((errors[0].line, errors[0].columns), (Some(2), Some((2, 6))));
 	}
 
 	#[test]
@@ -259,6 +695,7 @@ mod tests {
 
 		assert_eq!(enum_rule.verify("", "enum\n{\nVALUE\n}").len(), 0);
 		assert_eq!(enum_rule.verify("", "enum\n{\nVALUE, \t\nVALUE2\n}").len(), 0);
+		assert_eq!(enum_rule.verify("", "/*\n** enum\n** {\n** value\n** }\n*/").len(), 0);
 
 		assert_eq!(enum_rule.verify("", "enum\n{\nvalue\n}").len(), 1);
 		assert_eq!(enum_rule.verify("", "enum\n{\nValue\n}").len(), 1);
@@ -275,8 +712,62 @@ mod tests {
 		assert_eq!(static_variable.verify("", "static function(parameter...").len(), 0);
 		assert_eq!(static_variable.verify("", "int some_static_name;").len(), 0);
 		assert_eq!(static_variable.verify("", "//something static something").len(), 0);
+		assert_eq!(static_variable.verify("", "/*\n** static something;\n*/").len(), 0);
 
 		assert_eq!(static_variable.verify("", "static something;").len(), 1);
 		assert_eq!(static_variable.verify("", "static var = function(parameter);").len(), 1);
 	}
+
+	#[test]
+	fn pointer_symbol() {
+		let pointer_symbol = PointerSymbol::new();
+
+		assert_eq!(pointer_symbol.verify("", "char *g_p;\nchar **f(char *s, t_list **list);\nvoid (*g_f)(int *);").len(), 0);
+		assert_eq!(pointer_symbol.verify("", "int f(void)\n{\n    char    *s = (char*)a * b;\n    t_list  *node;\n\n    return (x * y);\n}").len(), 0);
+		assert_eq!(pointer_symbol.verify("", "struct s_a\n{\n    int     *const p;\n    char    a[N * 2];\n};").len(), 0);
+
+		assert_eq!(pointer_symbol.verify("", "char* g_p;").len(), 1);
+		assert_eq!(pointer_symbol.verify("", "char * g_p;").len(), 1);
+		assert_eq!(pointer_symbol.verify("", "char * * f(char* s, int *a);").len(), 2);
+		assert_eq!(pointer_symbol.verify("", "int f(void)\n{\n    int* a, * b;\n}").len(), 2);
+		assert_eq!(pointer_symbol.verify("", "typedef struct s_a\n{\n    int* p;\n} t_a;").len(), 1);
+
+		assert_eq!(pointer_symbol.verify("", "char *\n    g_p;\nchar */* c */g_q;").len(), 2);
+		assert_eq!(pointer_symbol.fix("", "char *\n    g_p;\nchar */* c */g_q;"), "char *\n    g_p;\nchar */* c */g_q;");
+		assert_eq!(pointer_symbol.fix("", "char* g_a, * * g_b;\nvoid (* g_f)(int);"), "char *g_a, **g_b;\nvoid (*g_f)(int);");
+		assert_eq!(pointer_symbol.fix("", "int f(char*s)\n{\n    t_list*    node;\n}"), "int f(char *s)\n{\n    t_list    *node;\n}");
+	}
+
+	#[test]
+	fn one_declaration_per_line() {
+		let one_declaration_per_line = OneDeclarationPerLine::new();
+
+		assert_eq!(one_declaration_per_line.verify("", "int g_a = f(1, 2);\nvoid (*g_f)(int, char);\nint g_t[2] = {1, 2};").len(), 0);
+		assert_eq!(one_declaration_per_line.verify("", "enum e_a\n{\n    A,\n    B\n};\nint f(int a, int b)\n{\n    g(a, b);\n}").len(), 0);
+
+		assert_eq!(one_declaration_per_line.verify("", "int g_a, g_b;").len(), 1);
+		assert_eq!(one_declaration_per_line.verify("", "struct s_a\n{\n    int a, b;\n    int (*f)(int, int), c;\n};").len(), 2);
+		assert_eq!(one_declaration_per_line.verify("", "int f(void)\n{\n    int a, b = g(1, 2), c;\n}").len(), 1);
+
+		assert_eq!(one_declaration_per_line.fix("", "int f(void)\n{\n    static int  a, *b = {0}, c;\n}"), "int f(void)\n{\n    static int  a;\n    static int *b = {0};\n    static int  c;\n}");
+		assert_eq!(one_declaration_per_line.fix("", "struct s_a\n{\n    char    *a, b[2];\n};"), "struct s_a\n{\n    char    *a;\n    char     b[2];\n};");
+		assert_eq!(one_declaration_per_line.fix("", "int g_a, /* b */ g_b;"), "int g_a, /* b */ g_b;");
+	}
+
+	#[test]
+	fn one_statement_per_line() {
+		let one_statement_per_line = OneStatementPerLine::new();
+
+		assert_eq!(one_statement_per_line.verify("", "int f(void)\n{\n    int a, b;\n\n    for (a = 0, b = 1; a < b; a++)\n        g(a, b);\n    do\n    {\n        a++;\n    } while (a);\n    return (0);\n}").len(), 0);
+		assert_eq!(one_statement_per_line.verify("", "int g_t[2] = {1, 2};\n#define F(a, b) ((a), (b))\n#define G(a) \\\n    do_a(a); \\\n    do_b(a)").len(), 0);
+		assert_eq!(one_statement_per_line.verify("", "switch (a)\n{\n    case A:\n        break;\n    default:\n        return (a ? 1 : 2);\n}").len(), 0);
+		assert_eq!(one_statement_per_line.verify("", "if (a)\n    b();\nelse if (c)\n    d();\nelse\n    e();").len(), 0);
+
+		assert_eq!(one_statement_per_line.verify("", "if (x) return (1);").len(), 1);
+		assert_eq!(one_statement_per_line.verify("", "int f(void)\n{\n    a = 1, b = 2;\n}").len(), 1);
+		assert_eq!(one_statement_per_line.verify("", "#define F(x) \\\n    x++; y++;").len(), 1);
+		assert_eq!(one_statement_per_line.verify("", "for (;;);\nelse a++;\ndo a++;\ncase 1: break;").len(), 4);
+		assert_eq!(one_statement_per_line.verify("", "a; b; c;").len(), 2);
+		assert_eq!(one_statement_per_line.verify("", "int f(void)\n{\n    a, , b;\n}").len(), 1);
+	}
 }
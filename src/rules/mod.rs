@@ -12,7 +12,188 @@ pub use self::function::*;
 pub use self::naming::*;
 pub use self::control_structures::*;
 
+use std::sync::OnceLock;
+
+use config::{Config, ConfigError};
+use diagnostic::Diagnostic;
+#[cfg(test)]
+use fix;
+use source::SourceFile;
+#[cfg(test)]
+use source::FileKinds;
+use suppression;
 
 pub trait Rule: Sync {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String>;
+	//Identifier used to refer to the rule, its structure name.
+	fn id(&self) -> &'static str;
+	//What the norm requires, with the configured values.
+	fn description(&self) -> String;
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic>;
+	//Problems with the run rather than with a checked file, printed once at its end.
+	fn warnings(&self) -> Vec<String> {
+		Vec::new()
+	}
+
+	#[cfg(test)]
+	fn verify(&self, filename: &str, content: &str) -> Vec<Diagnostic> {
+		self.check(&SourceFile::new(filename, FileKinds::default().kind(filename), content))
+	}
+
+	//Content once the fixes of a single check have been applied.
+	#[cfg(test)]
+	fn fix(&self, filename: &str, content: &str) -> String {
+		fix::apply(content, &self.verify(filename, content)).unwrap_or_else(|| String::from(content))
+	}
+}
+
+
+//Diagnostics of every rule on the file that are not suppressed, sorted by line.
+pub fn check_all(rules: &[Box<dyn Rule>], file: &SourceFile) -> Vec<Diagnostic> {
+	let mut diagnostics = Vec::new();
+	for rule in rules.iter() {
+		diagnostics.extend(rule.check(file));
+	}
+
+	let checked_rules: Vec<&str> = rules.iter().map(|rule| rule.id()).collect();
+	let mut diagnostics = suppression::apply(file, diagnostics, &checked_rules, known_ids());
+	//Stable, so diagnostics on the same place stay in the order of the rules.
+	diagnostics.sort_by_key(|d| (d.line, d.columns.map(|c| c.0)));
+
+	return diagnostics;
+}
+
+//Identifiers of every rule, whether it is selected or not.
+pub fn known_ids() -> &'static [&'static str] {
+	static IDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+	return IDS.get_or_init(|| from_config(&Config::default()).unwrap().iter().map(|rule| rule.id()).collect());
+}
+
+//Every rule, in the order they are run, configured by the '[rules]' table and the '[rules.<id>]' subtables.
+pub fn from_config(config: &Config) -> Result<Vec<Box<dyn Rule>>, ConfigError> {
+	//Searched after the directory of the including file, like '-I' directories.
+	let include = config.strings("rules.StructurePassedByAddress", "include")?.unwrap_or_default();
+	let include = include.iter().map(|directory| config.directory.join(directory)).collect();
+
+	let mut parameters: Vec<(&str, &str)> = vec![("StructurePassedByAddress", "include")];
+	let mut parameter = |id: &'static str, key: &'static str, default: usize| {
+		parameters.push((id, key));
+		config.integer(&format!("rules.{}", id), key, default)
+	};
+
+	let mut rules = Vec::<Box<dyn Rule>>::new();
+	//indentation.rs
+	rules.push(Box::new(LineSize::new(parameter("LineSize", "max", 80)?)));
+	rules.push(Box::new(SpaceIndentation::new()));
+	rules.push(Box::new(TrailingWhiteSpace::new()));
+	rules.push(Box::new(IndentationLevel::new(parameter("IndentationLevel", "spaces", 4)?)));
+	rules.push(Box::new(Comma::new()));
+	rules.push(Box::new(OperatorPadding::new()));
+	rules.push(Box::new(StructureFieldsIndentation::new()));
+
+	//naming.rs
+	rules.push(Box::new(Typedef::new()));
+	rules.push(Box::new(Global::new()));
+
+	//misc.rs
+	rules.push(Box::new(OwnLineBrace::new()));
+	rules.push(Box::new(MultiLinesComment::new()));
+	rules.push(Box::new(Goto::new()));
+	rules.push(Box::new(Enum::new()));
+	rules.push(Box::new(Semicolon::new()));
+	rules.push(Box::new(StaticVariable::new()));
+	rules.push(Box::new(PointerSymbol::new()));
+	rules.push(Box::new(OneDeclarationPerLine::new()));
+	rules.push(Box::new(OneStatementPerLine::new()));
+
+	//preprocessor.rs
+	rules.push(Box::new(PreprocessorOnFirstColumn::new()));
+	rules.push(Box::new(PreprocessorIndentation::new()));
+	rules.push(Box::new(PreprocessorComment::new()));
+	rules.push(Box::new(MultiLinesMacro::new()));
+	rules.push(Box::new(MacroName::new()));
+	rules.push(Box::new(MacroArguments::new()));
+	rules.push(Box::new(IncludePreprocessor::new()));
+	rules.push(Box::new(IncludeOrder::new()));
+	rules.push(Box::new(HeaderGuard::new()));
+
+	//function.rs
+	rules.push(Box::new(FunctionMaxCodeLines::new(parameter("FunctionMaxCodeLines", "max", 25)?)));
+	rules.push(Box::new(FunctionMaxArguments::new(parameter("FunctionMaxArguments", "max", 4)?)));
+	rules.push(Box::new(FunctionBlankLines::new()));
+	rules.push(Box::new(FunctionStartParenthesis::new()));
+	rules.push(Box::new(MaxFunctionsPerSourceFile::new(parameter("MaxFunctionsPerSourceFile", "max", 10)?)));
+	rules.push(Box::new(MaxExportedFunctions::new(parameter("MaxExportedFunctions", "max", 5)?)));
+	rules.push(Box::new(FunctionParametersIndentation::new()));
+	rules.push(Box::new(FunctionsPrototypeLocation::new()));
+	rules.push(Box::new(StructurePassedByAddress::new(include)));
+
+	//control_structures.rs
+	rules.push(Box::new(ControlStructuresIndentation::new()));
+	rules.push(Box::new(SpecialControlStructuresIndentation::new()));
+	rules.push(Box::new(SwitchDefaultCase::new()));
+	rules.push(Box::new(SwitchEnum::new()));
+	rules.push(Box::new(SwitchEnd::new()));
+
+	//Typos must not silently leave a rule enabled or with its default value.
+	let ids: Vec<&str> = rules.iter().map(|r| r.id()).collect();
+	for (id, line) in config.subtables("rules") {
+		if !ids.contains(&id) {
+			return Err(ConfigError::new(line, format!("Unknown rule '{}'.", id)));
+		}
+		for (key, line) in config.keys(&format!("rules.{}", id)) {
+			if !parameters.contains(&(id, key)) {
+				return Err(ConfigError::new(line, format!("Unknown parameter '{}' for rule '{}'.", key, id)));
+			}
+		}
+	}
+
+	let select = config.strings("rules", "select")?;
+	let ignore = config.strings("rules", "ignore")?.unwrap_or_default();
+	for (key, line) in config.keys("rules") {
+		if key != "select" && key != "ignore" {
+			return Err(ConfigError::new(line, format!("Unknown key '{}' in [rules].", key)));
+		}
+		let listed = if key == "select" { select.as_ref().unwrap() } else { &ignore };
+		if let Some(id) = listed.iter().find(|id| !ids.contains(&id.as_str())) {
+			return Err(ConfigError::new(line, format!("Unknown rule '{}'.", id)));
+		}
+	}
+
+	rules.retain(|rule| {
+		select.as_ref().is_none_or(|s| s.iter().any(|id| id == rule.id())) && !ignore.iter().any(|id| id == rule.id())
+	});
+
+	return Ok(rules);
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn configuration() {
+		let ids = |content: &str| from_config(&Config::parse(content).unwrap()).map(|rules| rules.iter().map(|r| r.id()).collect::<Vec<&str>>());
+
+		assert_eq!(ids("").unwrap().len(), 41);
+		assert_eq!(ids("[rules]\nselect = [\"LineSize\", \"Goto\"]").unwrap(), ["LineSize", "Goto"]);
+		assert!(!ids("[rules]\nignore = [\"Goto\"]").unwrap().contains(&"Goto"));
+
+		assert_eq!(ids("[rules]\nignore = [\"Gotoo\"]").unwrap_err().line, 2);
+		assert_eq!(ids("[rules.Gotoo]").unwrap_err().line, 1);
+		assert_eq!(ids("[rules.LineSize]\nmaximum = 3").unwrap_err().line, 2);
+		assert!(ids("[rules.StructurePassedByAddress]\ninclude = [\"include\"]").is_ok());
+		assert_eq!(ids("[rules.StructurePassedByAddress]\ninclude = \"include\"").unwrap_err().line, 2);
+
+		let config = Config::parse("[rules.LineSize]\nmax = 3").unwrap();
+		let rules = from_config(&config).unwrap();
+		assert_eq!(rules[0].verify("", "1234").len(), 1);
+	}
+
+	#[test]
+	fn truncated() {
+		let rules = from_config(&Config::default()).unwrap();
+		for content in ["enum {", "struct s {", "int f() {"].iter() {
+			check_all(&rules, &SourceFile::new("a.c", FileKinds::default().kind("a.c"), content));
+		}
+	}
 }
@@ -1,4 +1,8 @@
 use rules::Rule;
+use diagnostic::Diagnostic;
+use source::SourceFile;
+use lexer::TokenKind;
+use parser::Declaration;
 
 
 enum TypedefType {
@@ -20,76 +24,77 @@ impl Typedef {
 }
 
 impl Rule for Typedef {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
-
-		let mut indentation = 0;
-		let mut in_typedef = false;
-		let mut typedef_type = TypedefType::Normal;
+	fn id(&self) -> &'static str {
+		"Typedef"
+	}
 
-		for line in content.lines() {
-			if line.contains("{") {
-				indentation += 1;
-			}
-			if line.contains("}") && indentation > 0 {
-				indentation -= 1;
-			}
+	fn description(&self) -> String {
+		String::from("Type aliases must be prefixed by 's_', 'u_', 'e_', 't_' or 'f_' depending on the aliased type.")
+	}
 
-			if indentation <= 0 {
-				if line.contains("typedef") {
-					typedef_type = TypedefType::Normal;
-					if line.contains("struct") {
-						typedef_type = TypedefType::Struct;
-					}
-					else if line.contains("enum") {
-						typedef_type = TypedefType::Enum;
-					}
-					else if line.contains("union") {
-						typedef_type = TypedefType::Union;
-					}
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
 
-					in_typedef = true;
+		for declaration in file.declarations.iter() {
+			let typedef = match *declaration {
+				Declaration::Typedef(ref typedef) => typedef,
+				_ => continue
+			};
+
+			let code = &typedef.tokens;
+			let typedef_type = match code.iter().find(|t| ["struct", "enum", "union"].iter().any(|k| t.is_keyword(k))) {
+				Some(t) if t.text == "struct" => TypedefType::Struct,
+				Some(t) if t.text == "enum" => TypedefType::Enum,
+				Some(_) => TypedefType::Union,
+				None => TypedefType::Normal
+			};
+
+			let alias_token = match typedef.alias {
+				Some(ref alias) if alias.text.len() > 2 => alias,
+				_ => {
+					let last = code.last().unwrap_or(&code[0]);
+					errors.push(Diagnostic::new(self.id(), file.filename, last.line, "Anonymous typedef mustn't be used.")
+						.columns(last.column, last.column + last.text.len()));
+					continue;
 				}
-				if in_typedef && line.contains(";") {
-					let alias = line.split_whitespace().last().unwrap();
-					if alias.len() <= 2 {
-						errors.push(format!("[{}:{}]Anonymous typedef mustn't be used.", filename, line_number));
-					}
-					else if line.contains("typedef") && line.split_whitespace().count() == 3 {
-						let old = line.split_whitespace().nth(1).unwrap();
-						let new = line.split_whitespace().nth(2).unwrap();
-
-						if ["s_", "u_", "e_", "t_", "f_"].iter().any(|x| old.starts_with(x)) {
-							let start: String = old.chars().take(2).collect();
-							if !new.starts_with(&start) {
-								errors.push(format!("[{}:{}]Typedef '{}' must start with '{}'.", filename, line_number, alias, start));
-							}
-						}
+			};
+			let alias = alias_token.text;
+			let line = alias_token.line;
+			let columns = (alias_token.column, alias_token.column + alias.len());
+
+			if code.len() == 3 && code[1].kind == TokenKind::Identifier {
+				let old = code[1].text;
+				let new = code[2].text;
+
+				if ["s_", "u_", "e_", "t_", "f_"].iter().any(|x| old.starts_with(x)) {
+					let start: String = old.chars().take(2).collect();
+					if !new.starts_with(&start) {
+						errors.push(Diagnostic::new(self.id(), file.filename, line, format!("Typedef '{}' must start with '{}'.", alias, start))
+							.columns(columns.0, columns.1));
 					}
-					else {
-						match typedef_type {
-							TypedefType::Normal if !(alias.starts_with("t_") || alias.starts_with("f_")) => {
-								errors.push(format!("[{}:{}]Typedef '{}' must start with 't_' or 'f_'.", filename, line_number, alias));
-							},
-							TypedefType::Struct if !alias.starts_with("s_") => {
-								errors.push(format!("[{}:{}]Struct typedef '{}' must start with 's_'.", filename, line_number, alias));
-							},
-							TypedefType::Enum if !alias.starts_with("e_") => {
-								errors.push(format!("[{}:{}]Enum typedef '{}' must start with 'e_'.", filename, line_number, alias));
-							},
-							TypedefType::Union if !alias.starts_with("u_") => {
-								errors.push(format!("[{}:{}]Union typedef '{}' must start with 'u_'.", filename, line_number, alias));
-							},
-							_ => {}
-						}
-					}
-					in_typedef = false;
 				}
-				
-			} 
-
-			line_number += 1;
+			}
+			else {
+				match typedef_type {
+					TypedefType::Normal if !(alias.starts_with("t_") || alias.starts_with("f_")) => {
+						errors.push(Diagnostic::new(self.id(), file.filename, line, format!("Typedef '{}' must start with 't_' or 'f_'.", alias))
+							.columns(columns.0, columns.1));
+					},
+					TypedefType::Struct if !alias.starts_with("s_") => {
+						errors.push(Diagnostic::new(self.id(), file.filename, line, format!("Struct typedef '{}' must start with 's_'.", alias))
+							.columns(columns.0, columns.1));
+					},
+					TypedefType::Enum if !alias.starts_with("e_") => {
+						errors.push(Diagnostic::new(self.id(), file.filename, line, format!("Enum typedef '{}' must start with 'e_'.", alias))
+							.columns(columns.0, columns.1));
+					},
+					TypedefType::Union if !alias.starts_with("u_") => {
+						errors.push(Diagnostic::new(self.id(), file.filename, line, format!("Union typedef '{}' must start with 'u_'.", alias))
+							.columns(columns.0, columns.1));
+					},
+					_ => {}
+				}
+			}
 		}
 
 		return errors;
@@ -109,49 +114,35 @@ impl Global {
 }
 
 impl Rule for Global {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"Global"
+	}
 
-		let mut indentation = 0;
-		let mut in_macro = false;
+	fn description(&self) -> String {
+		String::from("Global variables must start with 'g_', with one global variable per file at most.")
+	}
 
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
 		let mut global_variable_found = 0;
 
-		for line in content.lines() {
-			if line.contains("{") {
-				indentation += 1;
-			}
-			if line.contains("}") && indentation > 0 {
-				indentation -= 1;
-			}
-
-			if indentation <= 0 {
-				if line.trim_left().starts_with("#") {
-					in_macro = line.trim_right().ends_with("\\");
-				}
-				else if !in_macro {
-					if line.contains(";") && !line.contains("}") && (!line.contains("(") || line.contains("=")) &&
-						(!line.trim_left().starts_with("//") && !line.trim_left().starts_with("**")){
-						global_variable_found += 1;
-						let macro_name = line.split("=").next().unwrap();
-						let macro_name = macro_name.split_whitespace().last().unwrap();
-
-						if !macro_name.starts_with("g_") {
-							errors.push(format!("[{}:{}]Macro name '{}' must start with 'g_'.", filename, line_number, macro_name));
-						}
-					}
-				}
-				else {
-					in_macro = line.trim_right().ends_with("\\");
+		for declaration in file.declarations.iter() {
+			let variable = match *declaration {
+				Declaration::Variable(ref variable) => variable,
+				_ => continue
+			};
+
+			for name in variable.names.iter() {
+				global_variable_found += 1;
+				if !name.text.starts_with("g_") {
+					errors.push(Diagnostic::new(self.id(), file.filename, name.line, format!("Global variable name '{}' must start with 'g_'.", name.text))
+						.columns(name.column, name.column + name.text.len()));
 				}
 			}
-
-			line_number += 1;
 		}
 
 		if global_variable_found > 1 {
-			errors.push(format!("[{}]One global variable per file maximum, found {}.", filename, global_variable_found));
+			errors.push(Diagnostic::file(self.id(), file.filename, format!("One global variable per file maximum, found {}.", global_variable_found)));
 		}
 
 		return errors;
@@ -180,6 +171,8 @@ mod tests {
 
 		assert_eq!(typedef.verify("", "typedef u_nion u_newunion;").len(), 0);
 		assert_eq!(typedef.verify("", "typedef s_nion u_newunion;").len(), 1);
+
+		assert_eq!(typedef.verify("", "typedef struct\n{\n    char *name; /* '{' */\n}\n s_truct;").len(), 0);
 	}
 
 	#[test]
@@ -197,5 +190,7 @@ mod tests {
 		assert_eq!(global.verify("", "int g_name = function();").len(), 0);
 		assert_eq!(global.verify("", "//Comment;").len(), 0);
 		assert_eq!(global.verify("", "/*\n**MultiLine Comment;\n*/").len(), 0);
+		assert_eq!(global.verify("", "void f()\n{\n    puts(\"}\");\n    int local;\n}").len(), 0);
+		assert_eq!(global.verify("", "struct s_forward;").len(), 0);
 	}
 }
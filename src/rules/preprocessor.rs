@@ -1,4 +1,10 @@
 use rules::Rule;
+use diagnostic::Diagnostic;
+use source::{FileKind, SourceFile};
+use lexer::TokenKind;
+
+//Directives followed by a macro name.
+const MACRO_DIRECTIVES: [&str; 4] = ["define", "undef", "ifdef", "ifndef"];
 
 pub struct PreprocessorOnFirstColumn {
 
@@ -11,16 +17,25 @@ impl PreprocessorOnFirstColumn {
 }
 
 impl Rule for PreprocessorOnFirstColumn {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"PreprocessorOnFirstColumn"
+	}
+
+	fn description(&self) -> String {
+		String::from("Preprocessor directives must start on the first column.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
-		for line in content.lines() {
-			if line.trim_left().starts_with("#") && !line.starts_with("#") {
-				errors.push(format!("[{}:{}]Preprocessor directive must start on the first column.", filename, line_number));
+		for token in file.tokens.iter().filter(|t| t.kind == TokenKind::Preprocessor) {
+			if token.column != 0 {
+				let mut diagnostic = Diagnostic::new(self.id(), file.filename, token.line, "Preprocessor directive must start on the first column.").columns(0, token.column + 1);
+				if file.content[token.offset - token.column..token.offset].trim().is_empty() {
+					diagnostic = diagnostic.edit(token.offset - token.column, token.offset, "");
+				}
+				errors.push(diagnostic);
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -41,31 +56,35 @@ impl PreprocessorIndentation {
 
 //Expect PreprocessorOnFirstColumn rule true for the given file
 impl Rule for PreprocessorIndentation {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"PreprocessorIndentation"
+	}
 
-		let mut current_indentation_level: usize = 0;
+	fn description(&self) -> String {
+		String::from("Preprocessor directives inside conditional ones must be indented by one space per level after '#'.")
+	}
 
-		for line in content.lines() {
-			if line.starts_with("#") {
-				if ["else", "endif"].iter().any(|x| line.contains(x)) {
-					current_indentation_level -= 1;
-				}
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
 
-				let line_without_first_char: String = line.chars().skip(1).collect();
+		let mut current_indentation_level: usize = 0;
 
-				let nb_whitespaces = line.len() - line_without_first_char.trim_left().len() - 1;
-				if nb_whitespaces != current_indentation_level {
-					errors.push(format!("[{}:{}]Expected {} white space after #, found {}.", filename, line_number, current_indentation_level, nb_whitespaces));
-				}
+		for token in file.tokens.iter().filter(|t| t.kind == TokenKind::Preprocessor) {
+			let directive = token.directive();
+			if ["else", "elif", "endif"].contains(&directive) {
+				current_indentation_level = current_indentation_level.saturating_sub(1);
+			}
 
-				if ["if", "else"].iter().any(|x| line.contains(x)) {
-					current_indentation_level += 1;
-				}
+			let nb_whitespaces = token.text.len() - directive.len() - 1;
+			if !directive.is_empty() && nb_whitespaces != current_indentation_level {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, format!("Expected {} white space after #, found {}.", current_indentation_level, nb_whitespaces))
+					.columns(token.column, token.column + token.text.len())
+					.edit(token.offset, token.end(), format!("#{}{}", " ".repeat(current_indentation_level), directive)));
 			}
 
-			line_number += 1;
+			if ["if", "ifdef", "ifndef", "else", "elif"].contains(&directive) {
+				current_indentation_level += 1;
+			}
 		}
 
 		return errors;
@@ -86,18 +105,30 @@ impl PreprocessorComment {
 
 //Expect PreprocessorOnFirstColumn rule true for the given file
 impl Rule for PreprocessorComment {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"PreprocessorComment"
+	}
+
+	fn description(&self) -> String {
+		String::from("#else and #endif must be followed by a comment describing their initial condition.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+		let tokens = &file.tokens;
 
-		for line in content.lines() {
-			if line.starts_with("#") && 
-				["endif", "else"].iter().any(|x| line.contains(x)) &&
-				!(line.contains("/*") && line.contains("*/")) && !line.contains("//")  {
-					errors.push(format!("[{}:{}]#else and #endif directives must have a comment describing their initial condition.", filename, line_number));
+		for (index, token) in tokens.iter().enumerate() {
+			if !["endif", "else"].contains(&token.directive()) {
+				continue;
 			}
 
-			line_number += 1;
+			let have_comment = tokens[index + 1..].iter()
+				.take_while(|t| t.preprocessor && t.kind != TokenKind::Preprocessor)
+				.any(|t| t.kind == TokenKind::Comment);
+			if !have_comment {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, "#else and #endif directives must have a comment describing their initial condition.")
+					.columns(token.column, token.column + token.text.len()));
+			}
 		}
 
 		return errors;
@@ -118,25 +149,40 @@ impl MultiLinesMacro {
 
 //Expect PreprocessorOnFirstColumn rule true for the given file
 impl Rule for MultiLinesMacro {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"MultiLinesMacro"
+	}
+
+	fn description(&self) -> String {
+		String::from("Line continuations of multi-line macros must be aligned.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
 		let mut in_multi_line_macro = false;
 		let mut alignement = 0;
 
-		for line in content.lines() {
-			if line.starts_with("#") {
-				if line.trim_right().ends_with("\\") {
-					in_multi_line_macro = true;
-					alignement = line.rfind("\\").unwrap();
-				}
+		for line in file.lines().iter() {
+			if line.first_code().is_some_and(|t| t.kind == TokenKind::Preprocessor) && line.text.trim_end().ends_with('\\') {
+				in_multi_line_macro = true;
+				alignement = line.text.rfind('\\').unwrap();
 			}
 
 			if in_multi_line_macro {
-				if line.trim_right().ends_with("\\") {
-					match line.rfind("\\") {
-						Some(index) if index != alignement => errors.push(format!("[{}:{}]Multi lines macro must have \\ aligned. Expected alignement on column {} got {}", filename, line_number, alignement, index)),
+				if line.text.trim_end().ends_with('\\') {
+					match line.text.rfind('\\') {
+						Some(index) if index != alignement => {
+							let mut diagnostic = Diagnostic::new(self.id(), file.filename, line.number, format!("Multi lines macro must have \\ aligned. Expected alignement on column {} got {}", alignement, index))
+								.columns(index, index + 1);
+
+							//Only padding is changed, a longer line needs the others to be moved by hand.
+							let end = line.text[..index].trim_end().len();
+							if end < alignement {
+								diagnostic = diagnostic.edit(line.offset + end, line.offset + index, " ".repeat(alignement - end));
+							}
+							errors.push(diagnostic);
+						},
 						_ => {}
 					}
 				}
@@ -144,8 +190,6 @@ impl Rule for MultiLinesMacro {
 					in_multi_line_macro = false;
 				}
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -167,26 +211,30 @@ impl MacroName {
 //Expect PreprocessorOnFirstColumn rule true for the given file,
 //and the macro name being on the first line of the macro (even for multiline macro).
 impl Rule for MacroName {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"MacroName"
+	}
+
+	fn description(&self) -> String {
+		String::from("Macro names must be entirely capitalized.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
-
-		for line in content.lines() {
-			if line.starts_with("#") && line.contains("def") {
-				//Match #ifndef, #ifdef and #define
-				
-				let words: Vec<&str> = line.split_whitespace().collect();
-				let macro_name = if words[0].contains("def") { words.get(1) } else { words.get(2) };
-				if let Some(macro_name) = macro_name {
-					let macro_name = macro_name.split("(").next().unwrap();
-
-					if macro_name != macro_name.to_uppercase() {
-						errors.push(format!("[{}:{}]Macro name must have be entirely capitalized. Expected \"{}\" got \"{}\"", filename, line_number, macro_name.to_uppercase(), macro_name));
-					}
-				}
+		let tokens = &file.tokens;
+
+		for (index, token) in tokens.iter().enumerate() {
+			if !MACRO_DIRECTIVES.contains(&token.directive()) {
+				continue;
 			}
 
-			line_number += 1;
+			match tokens.get(index + 1) {
+				Some(name) if name.preprocessor && name.kind == TokenKind::Identifier && name.text != name.text.to_uppercase() => {
+					errors.push(Diagnostic::new(self.id(), file.filename, name.line, format!("Macro name must have be entirely capitalized. Expected \"{}\" got \"{}\"", name.text.to_uppercase(), name.text))
+						.columns(name.column, name.column + name.text.len()));
+				},
+				_ => {}
+			}
 		}
 
 		return errors;
@@ -207,47 +255,42 @@ impl MacroArguments {
 
 //Expect PreprocessorOnFirstColumn rule true for the given file,
 impl Rule for MacroArguments {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+	fn id(&self) -> &'static str {
+		"MacroArguments"
+	}
 
+	fn description(&self) -> String {
+		String::from("Macro arguments must be capitalized.")
+	}
 
-		let mut in_macro = false;
-		let mut in_argument = false;
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		let mut errors = Vec::new();
+		let tokens = &file.tokens;
 
-		for line in content.lines() {
-			if line.starts_with("#") {
-				in_macro = true;
+		for (index, token) in tokens.iter().enumerate() {
+			if !MACRO_DIRECTIVES.contains(&token.directive()) || index + 2 >= tokens.len() {
+				continue;
 			}
 
-			if in_macro {
-				let mut line = line;
-				if line.contains("(") {
-					in_argument = true;
-					line = line.split("(").nth(1).unwrap();
-				}
-				line = line.split(")").next().unwrap();
-
-				if in_argument {
-					for arg in line.split(",").map(|x| x.trim()).filter(|x| !x.is_empty()) {
-						let temp_lower: String = arg.chars().skip(1).collect();
-						let good_macro_name = arg.chars().next().unwrap().to_string().to_uppercase() +
-							&(temp_lower).to_lowercase();
-					
-						if good_macro_name != arg {
-							errors.push(format!("[{}:{}]Macro arguments must have be capitalized. Expected \"{}\" got \"{}\"", filename, line_number, good_macro_name, arg));
-						}
-					}
-				}
-				
-				if line.contains(")") {
-					in_argument = false;
-				}
-
-				in_macro =line.contains("\\");
+			let name = &tokens[index + 1];
+			let open = &tokens[index + 2];
+			if !(name.preprocessor && open.preprocessor && open.is_punctuator("(") && open.offset == name.end()) {
+				continue;//Not a function-like macro.
 			}
 
-			line_number += 1;
+			let arguments = tokens[index + 3..].iter()
+				.take_while(|t| t.preprocessor && !t.is_punctuator(")"))
+				.filter(|t| t.kind == TokenKind::Identifier);
+			for arg in arguments {
+				let temp_lower: String = arg.text.chars().skip(1).collect();
+				let good_macro_name = arg.text.chars().next().unwrap().to_string().to_uppercase() +
+					&(temp_lower).to_lowercase();
+
+				if good_macro_name != arg.text {
+					errors.push(Diagnostic::new(self.id(), file.filename, arg.line, format!("Macro arguments must have be capitalized. Expected \"{}\" got \"{}\"", good_macro_name, arg.text))
+						.columns(arg.column, arg.column + arg.text.len()));
+				}
+			}
 		}
 
 		return errors;
@@ -269,43 +312,27 @@ impl IncludePreprocessor {
 
 //Expect PreprocessorOnFirstColumn and MultiLinesComment rules to be true for the given file,
 impl Rule for IncludePreprocessor {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
+	fn id(&self) -> &'static str {
+		"IncludePreprocessor"
+	}
+
+	fn description(&self) -> String {
+		String::from("All #include directives must appear at the start of the file.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
 
 		let mut have_seen_code = false;
-		let mut multiline_macro = false;
-		let mut multi_lines_comment = false;
 
-		for line in content.lines() {
-			if line.starts_with("#") {
-				if line.contains("\\") {
-					multiline_macro = true;
-				}
-				if line.contains("include") && have_seen_code {
-					errors.push(format!("[{}:{}]All #include directive must appear at the start of the file.", filename, line_number));
-				}
+		for token in file.tokens.iter() {
+			if token.directive() == "include" && have_seen_code {
+				errors.push(Diagnostic::new(self.id(), file.filename, token.line, "All #include directive must appear at the start of the file.")
+					.columns(token.column, token.column + token.text.len()));
 			}
-			else if !line.trim_left().starts_with("//")
-			{
-				if line.contains("/*") {
-					multi_lines_comment = true;
-				}
-				
-				if !multi_lines_comment {
-					if !multiline_macro && line.trim().len() > 0 {
-						have_seen_code = true;
-					}
-					if multiline_macro && !line.contains("\\") {
-						multiline_macro = false;
-					}
-				}
-				else if line.contains("*/") {
-					multi_lines_comment = false;
-				}
+			else if token.is_code() && !token.preprocessor {
+				have_seen_code = true;
 			}
-
-			line_number += 1;
 		}
 
 		return errors;
@@ -327,28 +354,39 @@ impl IncludeOrder {
 
 //Expect PreprocessorOnFirstColumn rule true for the given file,
 impl Rule for IncludeOrder {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		if !filename.contains(".h") {
+	fn id(&self) -> &'static str {
+		"IncludeOrder"
+	}
+
+	fn description(&self) -> String {
+		String::from("System headers must be included before local ones, in header files.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		if file.kind != FileKind::Header {
 			return Vec::new();//Not a header.
 		}
 
 		let mut errors = Vec::new();
-		let mut line_number: usize = 1;
+		let tokens = &file.tokens;
 
 		let mut have_seen_local_include = false;
 
-		for line in content.lines() {
-			if line.starts_with("#") && line.contains("include") {
-				if line.contains("\"") {
-					have_seen_local_include = true;
-				}
-				else if line.contains("<") && have_seen_local_include
-				{
-					errors.push(format!("[{}:{}]System headers must appear before locals one.", filename, line_number));
-				}
+		for (index, token) in tokens.iter().enumerate() {
+			if token.directive() != "include" {
+				continue;
 			}
 
-			line_number += 1;
+			match tokens.get(index + 1) {
+				Some(header) if header.kind == TokenKind::String => {
+					have_seen_local_include = true;
+				},
+				Some(header) if header.kind == TokenKind::HeaderName && have_seen_local_include => {
+					errors.push(Diagnostic::new(self.id(), file.filename, token.line, "System headers must appear before locals one.")
+						.columns(header.column, header.column + header.text.len()));
+				},
+				_ => {}
+			}
 		}
 
 		return errors;
@@ -369,27 +407,35 @@ impl HeaderGuard {
 
 //Expect PreprocessorOnFirstColumn rule true for the given file
 impl Rule for HeaderGuard {
-	fn verify(&self, filename: &str, content: &str) -> Vec<String> {
-		if !filename.contains(".h") {
+	fn id(&self) -> &'static str {
+		"HeaderGuard"
+	}
+
+	fn description(&self) -> String {
+		String::from("Headers must be protected against multiple inclusions.")
+	}
+
+	fn check(&self, file: &SourceFile) -> Vec<Diagnostic> {
+		if file.kind != FileKind::Header {
 			return Vec::new();//Not a header.
 		}
 
 		let mut errors = Vec::new();
-		let header_guard = filename.split("/").last().unwrap().replace(".", "_").to_uppercase() + "_";
+		let header_guard = file.filename.split("/").last().unwrap().replace(".", "_").to_uppercase() + "_";
 
 		let first_line = String::from("#ifndef ") + &header_guard;
 		let second_line = String::from("# define ") + &header_guard;
 
-		match content.lines().nth(0) {
+		match file.content.lines().nth(0) {
 			Some(line) if line == first_line => {},
 			_ => {
-				errors.push(format!("[{}:1]'{}' must appear on the first line.", filename, first_line));
+				errors.push(Diagnostic::new(self.id(), file.filename, 1, format!("'{}' must appear on the first line.", first_line)));
 			}
 		}
-		match content.lines().nth(1) {
+		match file.content.lines().nth(1) {
 			Some(line) if line == second_line => {},
 			_ => {
-				errors.push(format!("[{}:2]'{}' must appear on the second line.", filename, second_line));
+				errors.push(Diagnostic::new(self.id(), file.filename, 2, format!("'{}' must appear on the second line.", second_line)));
 			}
 		}
 
@@ -409,6 +455,8 @@ mod tests {
 		assert_eq!(preprocessor_on_first_column.verify("", " #\n\t#").len(), 2);
 		assert_eq!(preprocessor_on_first_column.verify("", "3#something").len(), 0);
 		assert_eq!(preprocessor_on_first_column.verify("", "adee#").len(), 0);
+
+		assert_eq!(preprocessor_on_first_column.fix("", "  #include <a.h>\n\t#define A"), "#include <a.h>\n#define A");
 	}
 
 	#[test]
@@ -421,6 +469,10 @@ mod tests {
 		assert_eq!(preprocessor_indentation.verify("", "#ifndef SOMETHING\n#define SOMETHING\n#endif").len(), 1);
 		assert_eq!(preprocessor_indentation.verify("", "# ifdef SOMETHING\n# define SOMETHING\n#endif").len(), 1);
 		assert_eq!(preprocessor_indentation.verify("", "#if 0\n# define SOMETHING\nsome code\n#endif").len(), 0);
+		assert_eq!(preprocessor_indentation.verify("", "#include \"elsewhere.h\"\n#define SOMETHING").len(), 0);
+		assert_eq!(preprocessor_indentation.verify("", "#if A\n#elif B\n# define C\n#endif\n#define D").len(), 0);
+
+		assert_eq!(preprocessor_indentation.fix("", "#ifdef A\n#define B\n#  endif"), "#ifdef A\n# define B\n#endif");
 	}
 
 	#[test]
@@ -451,6 +503,9 @@ mod tests {
 
 		assert_eq!(multi_lines_macro.verify("", "#define  zefrg (azdd ad) \\\n czdeff\\\n").len(), 1);
 		assert_eq!(multi_lines_macro.verify("", "#define  zefrg (azdd ad) \\\n czdeff                  \\\neececev").len(), 0);
+
+		assert_eq!(multi_lines_macro.fix("", "#define A    \\\n  a \\\n  b"), "#define A    \\\n  a          \\\n  b");
+		assert_eq!(multi_lines_macro.fix("", "#define A \\\n  longer_body \\\n  b"), "#define A \\\n  longer_body \\\n  b");
 	}
 
 	#[test]
@@ -464,6 +519,7 @@ mod tests {
 		assert_eq!(macro_name.verify("", "#define  Name").len(), 1);
 		assert_eq!(macro_name.verify("", "#define  name_lower_Case").len(), 1);
 		assert_eq!(macro_name.verify("", "#define  zefrg(TEST ad)\\\n czdeff").len(), 1);
+		assert_eq!(macro_name.verify("", "#if defined(HELLO)\n#include \"undefined.h\"").len(), 0);
 	}
 
 	#[test]
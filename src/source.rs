@@ -0,0 +1,251 @@
+use lexer;
+use lexer::{Token, TokenKind};
+use parser;
+use parser::{Declaration, Function};
+
+//What a character of the source file belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mask {
+	Code,
+	Comment,
+	String,//String and character literals, as well as <header> names.
+	Preprocessor//Directive, including its continuation lines.
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+	Source,
+	Header,
+	Unknown
+}
+
+
+//Extensions, without their dot, telling sources from headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileKinds {
+	pub sources: Vec<String>,
+	pub headers: Vec<String>
+}
+
+impl Default for FileKinds {
+	fn default() -> FileKinds {
+		FileKinds { sources: vec![String::from("c")], headers: vec![String::from("h")] }
+	}
+}
+
+impl FileKinds {
+	//From the extension of the file name only, so that "src.d/a.h" is a header and "a.c.bak" is unknown.
+	pub fn kind(&self, filename: &str) -> FileKind {
+		let name = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+		let extension = match name.rsplit_once('.') {
+			Some((_, extension)) => extension,
+			None => return FileKind::Unknown
+		};
+
+		if self.sources.iter().any(|e| e == extension) {
+			FileKind::Source
+		}
+		else if self.headers.iter().any(|e| e == extension) {
+			FileKind::Header
+		}
+		else {
+			FileKind::Unknown
+		}
+	}
+}
+
+
+//A file read once, shared by every rule.
+pub struct SourceFile<'a> {
+	pub filename: &'a str,
+	pub kind: FileKind,
+	pub content: &'a str,
+	pub tokens: Vec<Token<'a>>,
+	pub declarations: Vec<Declaration<'a>>,
+	texts: Vec<&'a str>,
+	offsets: Vec<usize>,
+	masks: Vec<Vec<Mask>>,
+	token_ranges: Vec<(usize, usize)>,
+	continuations: Vec<Option<TokenKind>>
+}
+
+impl<'a> SourceFile<'a> {
+	pub fn new(filename: &'a str, kind: FileKind, content: &'a str) -> SourceFile<'a> {
+		let tokens = lexer::tokenize(content);
+		let texts: Vec<&str> = content.lines().collect();
+		let offsets: Vec<usize> = content.split_inclusive('\n').scan(0, |offset, line| {
+			let start = *offset;
+			*offset += line.len();
+			Some(start)
+		}).collect();
+
+		let mut masks: Vec<Vec<Mask>> = texts.iter().map(|text| vec![Mask::Code; text.len()]).collect();
+		for token in tokens.iter().filter(|t| t.kind == TokenKind::Preprocessor) {
+			let mut index = token.line - 1;
+			let mut start = token.column;
+			while index < texts.len() {
+				fill(&mut masks[index], start, texts[index].len(), Mask::Preprocessor);
+				if !texts[index].trim_end().ends_with('\\') {
+					break;
+				}
+				index += 1;
+				start = 0;
+			}
+		}
+		for token in tokens.iter() {
+			let mask = match token.kind {
+				TokenKind::Comment => Mask::Comment,
+				TokenKind::String | TokenKind::Character | TokenKind::HeaderName => Mask::String,
+				_ => continue
+			};
+
+			let mut column = token.column;
+			for (index, part) in token.text.split('\n').enumerate() {
+				if let Some(line_mask) = masks.get_mut(token.line - 1 + index) {
+					let end = (column + part.len()).min(line_mask.len());
+					fill(line_mask, column, end, mask);
+				}
+				column = 0;
+			}
+		}
+
+		let mut token_ranges = Vec::new();
+		let mut continuations = Vec::new();
+		let mut index = 0;
+		let mut covered_until = 0;
+		let mut covered_kind = None;
+		for number in 1..texts.len() + 1 {
+			let start = index;
+			while index < tokens.len() && tokens[index].line == number {
+				index += 1;
+			}
+
+			continuations.push(if covered_until >= number { covered_kind } else { None });
+			for token in tokens[start..index].iter() {
+				if token.end_line() > number {
+					covered_until = token.end_line();
+					covered_kind = Some(token.kind);
+				}
+			}
+			token_ranges.push((start, index));
+		}
+
+		let declarations = parser::parse(&tokens);
+		SourceFile { filename, kind, content, tokens, declarations, texts, offsets, masks, token_ranges, continuations }
+	}
+
+	pub fn functions(&self) -> impl Iterator<Item = &Function<'a>> {
+		self.declarations.iter().filter_map(|declaration| match *declaration {
+			Declaration::Function(ref function) => Some(function),
+			_ => None
+		})
+	}
+
+	//There is exactly one Line per line of content.lines().
+	pub fn lines(&self) -> Vec<Line<'a, '_>> {
+		(0..self.texts.len()).map(|index| Line {
+			number: index + 1,
+			offset: self.offsets[index],
+			text: self.texts[index],
+			tokens: &self.tokens[self.token_ranges[index].0..self.token_ranges[index].1],
+			mask: &self.masks[index],
+			continuation: self.continuations[index]
+		}).collect()
+	}
+}
+
+fn fill(mask: &mut [Mask], start: usize, end: usize, value: Mask) {
+	if start < end {
+		for element in mask[start..end].iter_mut() {
+			*element = value;
+		}
+	}
+}
+
+
+pub struct Line<'a, 'b> {
+	pub number: usize,
+	pub offset: usize,//Byte offset of the start of the line in the content.
+	pub text: &'a str,
+	pub tokens: &'b [Token<'a>],//Tokens starting on this line.
+	pub mask: &'b [Mask],//One element per byte of text.
+	pub continuation: Option<TokenKind>//Kind of the multi lines token covering the start of this line, if any.
+}
+
+impl<'a, 'b> Line<'a, 'b> {
+	pub fn first_code(&self) -> Option<&'b Token<'a>> {
+		self.tokens.iter().find(|t| t.is_code())
+	}
+
+	pub fn has_code(&self) -> bool {
+		self.first_code().is_some()
+	}
+
+	//Empty, or only made of whitespaces that are not part of a comment.
+	pub fn is_blank(&self) -> bool {
+		self.text.trim().is_empty() && self.continuation != Some(TokenKind::Comment)
+	}
+
+	pub fn count_punctuator(&self, punctuator: &str) -> usize {
+		self.tokens.iter().filter(|t| t.is_punctuator(punctuator)).count()
+	}
+
+	pub fn starts_with_keyword(&self, keyword: &str) -> bool {
+		self.first_code().is_some_and(|t| t.is_keyword(keyword))
+	}
+
+	pub fn starts_with_punctuator(&self, punctuator: &str) -> bool {
+		self.first_code().is_some_and(|t| t.is_punctuator(punctuator))
+	}
+
+	pub fn is_preprocessor(&self) -> bool {
+		self.mask.contains(&Mask::Preprocessor)
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn lines() {
+		let file = SourceFile::new("", FileKind::Unknown, "a /*\n\n*/ b\n\"c\"");
+		let lines = file.lines();
+
+		assert_eq!(lines.len(), 4);
+		assert_eq!(lines[0].tokens.len(), 2);
+		assert_eq!(lines[1].continuation, Some(TokenKind::Comment));
+		assert!(!lines[1].is_blank());
+		assert_eq!(lines[2].continuation, Some(TokenKind::Comment));
+		assert_eq!(lines[2].tokens.len(), 1);
+		assert_eq!(lines[3].continuation, None);
+	}
+
+	#[test]
+	fn masks() {
+		use self::Mask::*;
+		let file = SourceFile::new("", FileKind::Unknown, "#define A \"a\" \\\n  1 /* c\n*/\nb('a');");
+		let lines = file.lines();
+
+		assert_eq!(lines[0].mask, [Preprocessor; 10].iter().chain([String; 3].iter()).chain([Preprocessor; 2].iter()).cloned().collect::<Vec<Mask>>().as_slice());
+		assert_eq!(lines[1].mask, &[Preprocessor, Preprocessor, Preprocessor, Preprocessor, Comment, Comment, Comment, Comment]);
+		assert_eq!(lines[2].mask, &[Comment, Comment]);
+		assert_eq!(lines[3].mask, &[Code, Code, String, String, String, Code, Code]);
+		assert!(lines[1].is_preprocessor());
+		assert!(!lines[3].is_preprocessor());
+	}
+
+	#[test]
+	fn kinds() {
+		let mut kinds = FileKinds::default();
+		assert_eq!(kinds.kind("main.c"), FileKind::Source);
+		assert_eq!(kinds.kind("./src.config/foo.h"), FileKind::Header);
+		assert_eq!(kinds.kind("my.c.bak"), FileKind::Unknown);
+		assert_eq!(kinds.kind("Makefile"), FileKind::Unknown);
+		assert_eq!(kinds.kind(".h"), FileKind::Header);
+
+		kinds.headers.push(String::from("inc"));
+		assert_eq!(kinds.kind("src/table.inc"), FileKind::Header);
+	}
+}
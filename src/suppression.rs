@@ -0,0 +1,167 @@
+use diagnostic::{Diagnostic, Severity};
+use lexer::TokenKind;
+use source::SourceFile;
+
+//Identifier of the diagnostics reporting suppressions that never matched.
+pub const UNUSED_ID: &str = "UnusedSuppression";
+
+const DISABLE_NEXT_LINE: &str = "norme-disable-next-line";
+const DISABLE: &str = "norme-disable";
+const ENABLE: &str = "norme-enable";
+
+
+//Diagnostics of a rule that are silenced on a range of lines by a comment directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+	pub rule: String,
+	pub line: usize,//Line of the directive.
+	pub columns: (usize, usize),//Of the directive comment on its line.
+	pub lines: (usize, usize),//First and last silenced lines, included.
+	pub until_end: bool//Never enabled again, the diagnostics of the whole file are silenced too.
+}
+
+impl Suppression {
+	fn matches(&self, diagnostic: &Diagnostic) -> bool {
+		diagnostic.rule == self.rule && diagnostic.line.map_or(self.until_end, |line| self.lines.0 <= line && line <= self.lines.1)
+	}
+}
+
+
+//Reads "/* norme-disable-next-line Rule */" and "/* norme-disable Rule */ ... /* norme-enable Rule */" directives.
+//Several rules can be given, separated by spaces or commas. A 'norme-disable' without its 'norme-enable' lasts until the end of the file,
+//and silences the diagnostics about the whole file, like the count of globals.
+pub fn parse(file: &SourceFile) -> Vec<Suppression> {
+	let mut suppressions = Vec::new();
+	let mut opened: Vec<Suppression> = Vec::new();
+	let last_line = file.content.lines().count().max(1);
+
+	for token in file.tokens.iter().filter(|t| t.kind == TokenKind::Comment) {
+		let text = if token.text.starts_with("//") {
+			&token.text[2..]
+		}
+		else {
+			token.text[2..].trim_end_matches("*/")
+		};
+
+		let mut words = text.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty());
+		let directive = words.next().unwrap_or("");
+		let columns = (token.column, token.column + token.text.lines().next().unwrap_or("").len());
+
+		for rule in words {
+			match directive {
+				DISABLE_NEXT_LINE => {
+					let line = token.end_line() + 1;
+					suppressions.push(Suppression { rule: String::from(rule), line: token.line, columns, lines: (line, line), until_end: false });
+				},
+				DISABLE if !opened.iter().any(|s| s.rule == rule) => {
+					opened.push(Suppression { rule: String::from(rule), line: token.line, columns, lines: (token.line, last_line), until_end: true });
+				},
+				ENABLE => {
+					if let Some(index) = opened.iter().position(|s| s.rule == rule) {
+						let mut suppression = opened.remove(index);
+						suppression.lines.1 = token.line;
+						suppression.until_end = false;
+						suppressions.push(suppression);
+					}
+				},
+				_ => {}
+			}
+		}
+	}
+	suppressions.extend(opened);
+	suppressions.sort_by_key(|s| (s.line, s.columns.0));
+
+	return suppressions;
+}
+
+//Removes the suppressed diagnostics, and reports the suppressions of the checked rules that removed nothing, as well as
+//those of rules that don't exist.
+pub fn apply(file: &SourceFile, diagnostics: Vec<Diagnostic>, checked_rules: &[&str], known_rules: &[&str]) -> Vec<Diagnostic> {
+	let suppressions = parse(file);
+	let mut used = vec![false; suppressions.len()];
+
+	let mut kept: Vec<Diagnostic> = diagnostics.into_iter().filter(|diagnostic| {
+		let mut suppressed = false;
+		for (suppression, used) in suppressions.iter().zip(used.iter_mut()) {
+			if suppression.matches(diagnostic) {
+				*used = true;
+				suppressed = true;
+			}
+		}
+		!suppressed
+	}).collect();
+
+	for (suppression, &used) in suppressions.iter().zip(used.iter()) {
+		let message = if !known_rules.contains(&suppression.rule.as_str()) {
+			format!("Suppression of unknown rule '{}'.", suppression.rule)
+		}
+		else if !used && checked_rules.contains(&suppression.rule.as_str()) {
+			format!("Suppression of '{}' never matched any diagnostic.", suppression.rule)
+		}
+		else {
+			continue;
+		};
+		let mut diagnostic = Diagnostic::new(UNUSED_ID, file.filename, suppression.line, message).columns(suppression.columns.0, suppression.columns.1);
+		diagnostic.severity = Severity::Warning;
+		kept.push(diagnostic);
+	}
+
+	return kept;
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rules::{Global, Goto, Rule};
+	use source::FileKind;
+
+	fn check(content: &str) -> Vec<Diagnostic> {
+		let file = SourceFile::new("main.c", FileKind::Source, content);
+		apply(&file, Goto::new().check(&file), &["Goto"], &["Goto", "Enum", "LineSize"])
+	}
+
+	#[test]
+	fn next_line() {
+		assert_eq!(check("/* norme-disable-next-line Goto */\ngoto end;").len(), 0);
+		assert_eq!(check("// norme-disable-next-line LineSize, Goto\ngoto end;").len(), 0);
+		assert_eq!(check("/* norme-disable-next-line Goto */\ngoto end;\ngoto end;").len(), 1);
+		assert_eq!(check("goto end;/* norme-disable-next-line Goto */").len(), 2);
+	}
+
+	#[test]
+	fn region() {
+		assert_eq!(check("/* norme-disable Goto */\ngoto end;\ngoto end;\n/* norme-enable Goto */").len(), 0);
+		assert_eq!(check("/* norme-disable Goto */\ngoto end;\n/* norme-enable Goto */\ngoto end;").len(), 1);
+		assert_eq!(check("goto end;\n/* norme-disable Goto */\ngoto end;").len(), 1);
+		assert_eq!(check("/* norme-disable Enum */\ngoto end;").len(), 1);
+	}
+
+	#[test]
+	fn unused() {
+		let errors = check("/* norme-disable-next-line Goto */\nint x;");
+		assert_eq!(errors.len(), 1);
+		assert_eq!((errors[0].rule, errors[0].line, errors[0].severity), (UNUSED_ID, Some(1), Severity::Warning));
+
+		assert_eq!(check("/* norme-disable-next-line LineSize */\nint x;").len(), 0);
+		assert_eq!(check("/* norme-disable Goto */\n/* norme-enable Goto */").len(), 1);
+
+		let errors = check("/* norme-disable-next-line Gotoo */\ngoto end;");
+		assert_eq!(errors.len(), 2);
+		assert_eq!((errors[1].rule, errors[1].message.as_str()), (UNUSED_ID, "Suppression of unknown rule 'Gotoo'."));
+	}
+
+	#[test]
+	fn whole_file() {
+		let content = "int g_a;\nint g_b;";
+		let file = SourceFile::new("main.c", FileKind::Source, content);
+		let global = |content: &str| {
+			let file = SourceFile::new("main.c", FileKind::Source, content);
+			apply(&file, Global::new().check(&file), &["Global"], &["Global"])
+		};
+		assert_eq!(Global::new().check(&file).iter().filter(|d| d.line.is_none()).count(), 1);
+
+		assert_eq!(global(&format!("/* norme-disable Global */\n{}", content)).len(), 0);
+		assert_eq!(global(&format!("/* norme-disable Global */\n/* norme-enable Global */\n{}", content)).len(), 2);
+	}
+}
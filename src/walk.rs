@@ -0,0 +1,301 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use config::{Config, ConfigError};
+use ignore::{self, Ignore};
+use source::FileKinds;
+
+const TABLE: &str = "files";
+
+
+//Files to check, found from the paths given on the command line, in a sorted order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Walk {
+	pub filenames: Vec<String>,
+	pub skipped: usize,//Files of the walked directories without a checked extension.
+	pub errors: Vec<String>,//Paths that couldn't be read, with the reason.
+	pub warnings: Vec<String>//Links not followed as they would walk a directory again.
+}
+
+
+//What is carried along the walk of the given paths.
+struct State {
+	walk: Walk,
+	excludes: Ignore,
+	ignores: Vec<Ignore>,//Of the walked directories and their parents, from the outermost.
+	visited: HashSet<Identity>,//Of the walked directories.
+	parents: Vec<Identity>//Same, for the directories being walked only.
+}
+
+//Device and inode of a directory, or its canonical path where there are no inodes.
+#[cfg(unix)]
+type Identity = (u64, u64);
+#[cfg(not(unix))]
+type Identity = std::path::PathBuf;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Walker {
+	pub kinds: FileKinds,
+	extensions: Vec<String>,//Without their dot.
+	pub excludes: Vec<String>,//Globs relative to the current directory, with the syntax of the ignore files.
+	pub gitignore: bool,//Whether the '.gitignore' files are read along the '.normeignore' ones.
+	pub follow_symlinks: bool,//For the links found in directories, the given paths are always followed.
+	pub max_depth: Option<usize>//Of the files found, the given paths being at 0.
+}
+
+impl Walker {
+	pub fn new(extensions: Vec<String>) -> Walker {
+		Walker { kinds: FileKinds::default(), extensions, excludes: Vec::new(), gitignore: false, follow_symlinks: false, max_depth: None }
+	}
+
+	//"[files] sources" and "headers", "c" and "h" by default. "extensions", those of both by default, and "gitignore",
+	//false by default.
+	pub fn from_config(config: &Config) -> Result<Walker, ConfigError> {
+		for (key, line) in config.keys(TABLE) {
+			if !["sources", "headers", "extensions", "gitignore"].contains(&key) {
+				return Err(ConfigError::new(line, format!("Unknown key '{}' in [{}].", key, TABLE)));
+			}
+		}
+
+		let extensions = |key: &str| -> Result<Option<Vec<String>>, ConfigError> {
+			Ok(config.strings(TABLE, key)?.map(|e| e.iter().map(|e| String::from(e.trim_start_matches('.'))).collect()))
+		};
+		let default = FileKinds::default();
+		let kinds = FileKinds {
+			sources: extensions("sources")?.unwrap_or(default.sources),
+			headers: extensions("headers")?.unwrap_or(default.headers)
+		};
+
+		let mut walker = Walker::new(extensions("extensions")?.unwrap_or([kinds.sources.clone(), kinds.headers.clone()].concat()));
+		walker.kinds = kinds;
+		walker.gitignore = config.boolean(TABLE, "gitignore", false)?;
+		return Ok(walker);
+	}
+
+	//Files given by name are always checked unless excluded. In directories, only the ones with a checked extension
+	//are, and hidden directories and what the ignore files list are skipped. A directory is never walked twice.
+	pub fn walk(&self, paths: &[String]) -> Walk {
+		let current = env::current_dir().and_then(fs::canonicalize).unwrap_or_default();
+		let mut state = State {
+			walk: Walk::default(),
+			excludes: Ignore::new(&current, &self.excludes),
+			ignores: Vec::new(),
+			visited: HashSet::new(),
+			parents: Vec::new()
+		};
+
+		for path in paths.iter() {
+			let absolute = match fs::canonicalize(path) {
+				Ok(absolute) => absolute,
+				Err(error) => {
+					state.walk.errors.push(format!("{}: {}", path, error));
+					continue;
+				}
+			};
+			//The ignore files of the parent directories apply too.
+			state.ignores.clear();
+			for directory in absolute.ancestors().skip(1).collect::<Vec<&Path>>().into_iter().rev() {
+				state.ignores.extend(self.ignore_files(directory));
+			}
+			self.add(&mut state, path, &absolute, 0);
+		}
+
+		return state.walk;
+	}
+
+	fn add(&self, state: &mut State, pathname: &str, absolute: &Path, depth: usize) {
+		let given = depth == 0;
+		let path = Path::new(pathname);
+		let metadata = if given || self.follow_symlinks { fs::metadata(path) } else { fs::symlink_metadata(path) };
+		let metadata = match metadata {
+			Ok(metadata) => metadata,
+			Err(error) => return state.walk.errors.push(format!("{}: {}", pathname, error))
+		};
+		let is_directory = metadata.is_dir();
+		if state.excludes.matched(absolute, is_directory) == Some(true) || (!given && ignore::is_ignored(&state.ignores, absolute, is_directory)) {
+			return;
+		}
+
+		if metadata.is_file() {
+			if given || self.is_checked(path) {
+				state.walk.filenames.push(String::from(pathname));
+			}
+			else {
+				state.walk.skipped += 1;
+			}
+		}
+		else if is_directory {
+			if !given && path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+				return;
+			}
+			if self.max_depth.is_some_and(|max| depth >= max) {
+				return;
+			}
+			let identity = identity(path, &metadata);
+			if let Some(ref identity) = identity {
+				if state.parents.contains(identity) {
+					return state.walk.warnings.push(format!("{}: Symbolic link to one of its parent directories, not followed.", pathname));
+				}
+				if !state.visited.insert(identity.to_owned()) {
+					return;
+				}
+			}
+
+			let mut entries = Vec::new();
+			match path.read_dir() {
+				Ok(read) => for entry in read {
+					match entry {
+						Ok(entry) => entries.push(entry),
+						Err(error) => state.walk.errors.push(format!("{}: {}", pathname, error))
+					}
+				},
+				Err(error) => return state.walk.errors.push(format!("{}: {}", pathname, error))
+			}
+			entries.sort_by_key(|entry| entry.file_name());
+
+			let count = state.ignores.len();
+			state.ignores.extend(self.ignore_files(absolute));
+			let identified = identity.is_some();
+			state.parents.extend(identity);
+			for entry in entries.iter() {
+				match entry.path().to_str() {
+					Some(name) => self.add(state, name, &absolute.join(entry.file_name()), depth + 1),
+					None => state.walk.errors.push(format!("{}: Path isn't valid UTF-8, it can't be checked.", entry.path().display()))
+				}
+			}
+			if identified {
+				state.parents.pop();
+			}
+			state.ignores.truncate(count);
+		}
+	}
+
+	fn ignore_files(&self, directory: &Path) -> Vec<Ignore> {
+		let mut ignores = Vec::new();
+		if self.gitignore {
+			ignores.extend(Ignore::load(directory, ignore::GITIGNORE));
+		}
+		ignores.extend(Ignore::load(directory, ignore::FILENAME));
+
+		return ignores;
+	}
+
+	fn is_checked(&self, path: &Path) -> bool {
+		path.extension().is_some_and(|extension| self.extensions.iter().any(|e| extension == e.as_str()))
+	}
+}
+
+#[cfg(unix)]
+fn identity(_: &Path, metadata: &fs::Metadata) -> Option<Identity> {
+	use std::os::unix::fs::MetadataExt;
+	Some((metadata.dev(), metadata.ino()))
+}
+
+//Links and junctions are resolved by the canonical path, so a directory reached again has the same one.
+#[cfg(not(unix))]
+fn identity(path: &Path, _: &fs::Metadata) -> Option<Identity> {
+	fs::canonicalize(path).ok()
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+	use std::process;
+
+	#[test]
+	fn extensions() {
+		let root = env::temp_dir().join(format!("norme-walk-{}", process::id()));
+		for directory in ["src", ".git", "src/.hidden"].iter() {
+			fs::create_dir_all(root.join(directory)).unwrap();
+		}
+		for file in ["Makefile", "src/a.c", "src/a.h", "src/a.o", "src/b.c.bak", ".git/c.c", "src/.hidden/d.c"].iter() {
+			fs::write(root.join(file), "").unwrap();
+		}
+		let root_name = root.to_str().unwrap();
+
+		let walk = Walker::new(vec![String::from("c"), String::from("h")]).walk(&[String::from(root_name)]);
+		assert_eq!(walk.filenames, [format!("{}/src/a.c", root_name), format!("{}/src/a.h", root_name)]);
+		assert_eq!((walk.skipped, walk.errors.len()), (3, 0));
+
+		let walk = Walker::new(vec![String::from("c")]).walk(&[format!("{}/src/a.o", root_name), format!("{}/missing", root_name)]);
+		assert_eq!((walk.filenames.len(), walk.skipped, walk.errors.len()), (1, 0, 1));
+
+		let config = Config::parse("[files]\nextensions = [\".c\", \"inc\"]\n").unwrap();
+		assert_eq!(Walker::from_config(&config), Ok(Walker::new(vec![String::from("c"), String::from("inc")])));
+		let config = Config::parse("[files]\nheaders = [\"h\", \".hh\"]\n").unwrap();
+		let walker = Walker::from_config(&config).unwrap();
+		assert_eq!((walker.kinds.headers.len(), walker.extensions), (2, vec![String::from("c"), String::from("h"), String::from("hh")]));
+		assert!(Walker::from_config(&Config::parse("[files]\nextension = [\"c\"]\n").unwrap()).is_err());
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn ignores() {
+		let root = env::temp_dir().join(format!("norme-ignore-{}", process::id()));
+		for directory in ["src/gen", "vendor"].iter() {
+			fs::create_dir_all(root.join(directory)).unwrap();
+		}
+		for file in ["src/a.c", "src/b.c", "src/gen/parser.c", "src/gen/keep.c", "vendor/lib.c"].iter() {
+			fs::write(root.join(file), "").unwrap();
+		}
+		fs::write(root.join(".normeignore"), "/vendor/\n").unwrap();
+		fs::write(root.join(".gitignore"), "b.c\n").unwrap();
+		fs::write(root.join("src/gen/.normeignore"), "*.c\n!keep.c\n").unwrap();
+		let root_name = root.to_str().unwrap();
+
+		let mut walker = Walker::new(vec![String::from("c")]);
+		assert_eq!(walker.walk(&[String::from(root_name)]).filenames, [format!("{}/src/a.c", root_name), format!("{}/src/b.c", root_name), format!("{}/src/gen/keep.c", root_name)]);
+
+		walker.gitignore = true;
+		walker.excludes = vec![String::from("gen/")];
+		assert_eq!(walker.walk(&[format!("{}/src", root_name)]).filenames, [format!("{}/src/a.c", root_name)]);
+		assert_eq!(walker.walk(&[format!("{}/vendor/lib.c", root_name)]).filenames.len(), 1);
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn links() {
+		use std::ffi::OsStr;
+		use std::os::unix::ffi::OsStrExt;
+		use std::os::unix::fs::symlink;
+
+		let root = env::temp_dir().join(format!("norme-links-{}", process::id()));
+		fs::create_dir_all(root.join("src/deep/deeper")).unwrap();
+		for file in ["src/b.c", "src/a.c", "src/deep/c.c", "src/deep/deeper/d.c", "other.c"].iter() {
+			fs::write(root.join(file), "").unwrap();
+		}
+		symlink(root.join("src"), root.join("src/deep/loop")).unwrap();
+		symlink(root.join("other.c"), root.join("src/link.c")).unwrap();
+		let root_name = root.to_str().unwrap();
+		let src = format!("{}/src", root_name);
+		let given = [src.clone()];
+
+		let mut walker = Walker::new(vec![String::from("c")]);
+		let walk = walker.walk(&given);
+		assert_eq!(walk.filenames, [format!("{}/a.c", src), format!("{}/b.c", src), format!("{}/deep/c.c", src), format!("{}/deep/deeper/d.c", src)]);
+		assert!(walk.warnings.is_empty());
+
+		walker.follow_symlinks = true;
+		let walk = walker.walk(&given);
+		assert_eq!(walk.filenames.len(), 5);
+		assert_eq!(walk.warnings, [format!("{}/deep/loop: Symbolic link to one of its parent directories, not followed.", src)]);
+
+		walker.max_depth = Some(1);
+		assert_eq!(walker.walk(&given).filenames, [format!("{}/a.c", src), format!("{}/b.c", src), format!("{}/link.c", src)]);
+		walker.max_depth = Some(0);
+		assert_eq!(walker.walk(&[src.clone(), format!("{}/other.c", root_name)]).filenames, [format!("{}/other.c", root_name)]);
+
+		fs::write(root.join(OsStr::from_bytes(b"src/bad\xE9.c")), "").unwrap();
+		assert_eq!(Walker::new(vec![String::from("c")]).walk(&[src]).errors.len(), 1);
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+}